@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|kdl: &str| {
+    facet_kdl_fuzz::from_str(kdl);
+});
@@ -0,0 +1,90 @@
+//! Representative types for the fuzz targets to deserialize arbitrary KDL into.
+
+use facet::Facet;
+
+/// A config file made up of nested nodes, arguments, properties and defaults.
+#[derive(Debug, Facet)]
+pub struct Config {
+    #[facet(child)]
+    pub server: Server,
+    #[facet(child, default)]
+    pub name: String,
+    #[facet(child, default)]
+    pub retries: u8,
+}
+
+#[derive(Debug, Facet)]
+pub struct Server {
+    #[facet(argument)]
+    pub host: String,
+    #[facet(property)]
+    pub port: u16,
+    #[facet(property, default)]
+    pub weight: f64,
+    #[facet(property, default)]
+    pub enabled: bool,
+    #[facet(child, default)]
+    pub initial: char,
+}
+
+/// A struct whose fields are read from positional arguments only.
+#[derive(Debug, Facet)]
+pub struct Point {
+    #[facet(child)]
+    pub point: Coordinates,
+}
+
+#[derive(Debug, Facet)]
+pub struct Coordinates {
+    pub x: i64,
+    pub y: i64,
+    pub label: Option<String>,
+}
+
+/// A recursive enum, whose nodes can be nested as deep as the input likes.
+#[derive(Debug, Facet)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+pub enum Step {
+    Run(String),
+    Parallel(Vec<Step>),
+    Checkpoint,
+}
+
+#[derive(Debug, Facet)]
+pub struct Pipeline {
+    #[facet(argument)]
+    pub name: String,
+    pub steps: Vec<Step>,
+    #[facet(child, untagged, default)]
+    pub fallback: Option<Retry>,
+}
+
+/// A recursive untagged enum, so every level of nesting is tried against each variant in turn.
+#[derive(Debug, Facet)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+pub enum Retry {
+    Times(u8),
+    Nested {
+        #[facet(children, untagged)]
+        retries: Vec<Retry>,
+    },
+}
+
+/// Deserializes `kdl` into each of the representative types with `from_str`.
+pub fn from_str(kdl: &str) {
+    let _ = facet_kdl::from_str::<Config>(kdl);
+    let _ = facet_kdl::from_str::<Point>(kdl);
+    let _ = facet_kdl::from_str::<u32>(kdl);
+    let _ = facet_kdl::from_str::<Vec<String>>(kdl);
+    let _ = facet_kdl::from_str_nodes::<Server>(kdl, "server");
+    let _ = facet_kdl::from_str::<Vec<Pipeline>>(kdl);
+}
+
+/// Deserializes `kdl` into each of the representative types with `from_str_all_errors`.
+pub fn from_str_all_errors(kdl: &str) {
+    let _ = facet_kdl::from_str_all_errors::<Config>(kdl);
+    let _ = facet_kdl::from_str_all_errors::<Point>(kdl);
+    let _ = facet_kdl::from_str_all_errors::<Vec<Pipeline>>(kdl);
+}
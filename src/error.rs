@@ -0,0 +1,707 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    sync::Arc,
+};
+
+use facet_core::{Def, Shape, Type, UserType};
+use facet_reflect::ReflectError;
+use kdl::{KdlError as KdlParseError, KdlNode, KdlValue};
+
+/// Error type for KDL deserialization.
+///
+/// Its `Display` output includes a plain-text snippet of the KDL that caused the error, whichever features are enabled.
+#[derive(Debug)]
+pub struct KdlError {
+    // NOTE: The kind is boxed to keep `Result<T, KdlError>` small, since some kinds hold whole KDL values
+    kind: Box<KdlErrorKind>,
+    span: Option<Span>,
+    source_code: Option<Arc<String>>,
+    path: Option<Path>,
+}
+
+impl KdlError {
+    /// What went wrong.
+    pub fn kind(&self) -> &KdlErrorKind {
+        &self.kind
+    }
+
+    /// Where in the KDL source text the error happened, if that's known.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// The path to the value that was being deserialized when the error happened, like `process[1].env.PORT`.
+    ///
+    /// This is `None` for errors about the document as a whole.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref()
+    }
+
+    /// Points this error at `span` in `source_code`, unless it already points somewhere more precise.
+    pub(crate) fn located(mut self, span: Span, source_code: &Arc<String>) -> Self {
+        if self.span.is_none() {
+            self.span = Some(span);
+            self.source_code = Some(Arc::clone(source_code));
+        }
+        self
+    }
+
+    /// Records `path` as the place in the document this error happened, unless it already has a more precise one.
+    pub(crate) fn at_path(mut self, path: &Path) -> Self {
+        if self.path.is_none() && !path.0.is_empty() {
+            self.path = Some(path.clone());
+        }
+        self
+    }
+}
+
+impl Display for KdlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+        let kind = &self.kind;
+        write!(f, "{kind}")?;
+
+        if let Some(path) = &self.path {
+            write!(f, "\n  in `{path}`")?;
+        }
+        if let Some(span) = &self.span {
+            write!(f, "\n  --> line {}, column {}", span.line, span.column)?;
+            if let Some(source_code) = &self.source_code {
+                span.write_snippet(f, source_code)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+impl Error for KdlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &*self.kind {
+            KdlErrorKind::Parse(kdl_error) => Some(kdl_error),
+            KdlErrorKind::Reflect(reflect_error) => Some(reflect_error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for KdlError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(self.kind.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.kind
+            .help()
+            .map(|help| Box::new(help) as Box<dyn Display>)
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        self.source_code
+            .as_deref()
+            .map(|source_code| source_code as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let span = self.span?;
+        let label =
+            miette::LabeledSpan::new(Some(self.kind.label().to_owned()), span.offset, span.len);
+        Some(Box::new(std::iter::once(label)))
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn miette::Diagnostic> + 'a>> {
+        // NOTE: `kdl-rs` already produces nicely labelled diagnostics for syntax errors, so pass those straight through
+        match &*self.kind {
+            KdlErrorKind::Parse(kdl_error) => Some(Box::new(
+                kdl_error
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic as &dyn miette::Diagnostic),
+            )),
+            // NOTE: Each attempt at an untagged enum has its own location, which is worth showing alongside this one
+            KdlErrorKind::NoMatchingVariant { rejections, .. } => Some(Box::new(
+                rejections
+                    .iter()
+                    .map(|(_, error)| error as &dyn miette::Diagnostic),
+            )),
+            _ => None,
+        }
+    }
+}
+
+// NOTE: Errors start out without a location, which `located` adds once the span they belong to is known
+impl<K: Into<KdlErrorKind>> From<K> for KdlError {
+    fn from(value: K) -> Self {
+        let kind = Box::new(value.into());
+        KdlError {
+            kind,
+            span: None,
+            source_code: None,
+            path: None,
+        }
+    }
+}
+
+/// The different kinds of [`KdlError`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum KdlErrorKind {
+    /// Some KDL couldn't be turned into the Rust value it was meant to fill.
+    Mismatch {
+        /// Where the value was meant to go.
+        target: Target,
+        /// The type of the value that was expected.
+        expected: &'static Shape,
+        /// What was found instead.
+        found: Found,
+    },
+    /// A node had more arguments than the type it was filling has fields for.
+    UnexpectedArgument {
+        /// The type the node was filling.
+        parent: &'static Shape,
+        /// The position of the extra argument.
+        index: usize,
+        /// The extra argument.
+        found: Found,
+    },
+    /// A field that's filled from a fixed number of arguments was given a different number of them.
+    Arity {
+        /// The type the field belongs to.
+        parent: &'static Shape,
+        /// The name of the field.
+        field: String,
+        /// How many arguments the field needs.
+        expected: usize,
+        /// How many arguments were found.
+        found: usize,
+    },
+    /// A node or property name didn't match any field of the type it was filling.
+    UnknownField {
+        /// The type the node or property was filling.
+        parent: &'static Shape,
+        /// The kind of KDL construct the name came from.
+        role: Role,
+        /// The name that didn't match anything.
+        name: String,
+        /// Fields with similar names, closest first.
+        suggestions: Vec<&'static str>,
+    },
+    /// A node or value didn't name any of the variants of the enum it was filling.
+    UnknownVariant {
+        /// The enum being filled.
+        shape: &'static Shape,
+        /// The name that didn't match any variant.
+        name: String,
+        /// The names of every variant.
+        expected: Vec<&'static str>,
+    },
+    /// A node or value didn't fit any of the variants of an untagged enum.
+    NoMatchingVariant {
+        /// The enum being filled.
+        shape: &'static Shape,
+        /// Why each variant didn't fit, in the order they were tried.
+        rejections: Vec<(&'static str, KdlError)>,
+    },
+    /// An integer didn't match the discriminant of any of the variants of the enum it was filling.
+    UnknownDiscriminant {
+        /// The enum being filled.
+        shape: &'static Shape,
+        /// The integer that didn't match any variant.
+        value: i128,
+        /// The discriminants of every unit variant.
+        expected: Vec<i64>,
+    },
+    /// Some fields weren't given a value anywhere in the document.
+    ///
+    /// Each missing field is described by where it should have come from, like "argument #0 of `process`".
+    MissingNodes(Vec<String>),
+    /// A node in a list had a different name from the one every element's node was meant to have.
+    UnexpectedNode {
+        /// The name every node was meant to have.
+        expected: String,
+        /// The name of the node that was found instead.
+        found: String,
+    },
+    /// Nodes and values were nested deeper than the given limit, which guards against overflowing the stack.
+    TooDeep(usize),
+    /// The type being deserialized can't be read from a whole KDL document.
+    UnsupportedRoot(&'static Shape),
+    /// The input wasn't valid KDL.
+    Parse(KdlParseError),
+    /// The value being deserialized couldn't be built.
+    Reflect(ReflectError),
+}
+
+impl Display for KdlErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KdlErrorKind::Mismatch {
+                target,
+                expected,
+                found,
+            } => {
+                let Target {
+                    role,
+                    parent,
+                    field,
+                } = target;
+                match role {
+                    Role::Argument(index) => write!(
+                        f,
+                        "argument #{index} (`{field}`) of `{parent}` should be `{expected}`, but found {found}"
+                    ),
+                    Role::Property => write!(
+                        f,
+                        "property `{field}` of `{parent}` should be `{expected}`, but found {found}"
+                    ),
+                    Role::Child => {
+                        write!(f, "child node `{field}` of `{parent}` should ")?;
+                        write_node_layout(f, expected)?;
+                        write!(f, ", but found {found}")
+                    }
+                }
+            }
+            KdlErrorKind::UnexpectedArgument {
+                parent,
+                index,
+                found,
+            } => write!(
+                f,
+                "`{parent}` has no field for argument #{index}, but found {found}"
+            ),
+            KdlErrorKind::Arity {
+                parent,
+                field,
+                expected,
+                found,
+            } => {
+                let plural = if *expected == 1 { "" } else { "s" };
+                write!(
+                    f,
+                    "`{field}` of `{parent}` takes {expected} argument{plural}, but found {found}"
+                )
+            }
+            KdlErrorKind::UnknownField {
+                parent,
+                role,
+                name,
+                suggestions,
+            } => {
+                let role = match role {
+                    Role::Argument(_) => "argument",
+                    Role::Property => "property",
+                    Role::Child => "child node",
+                };
+                write!(f, "unknown {role} `{name}` on `{parent}`")?;
+
+                if let [rest @ .., last] = suggestions.as_slice() {
+                    write!(f, "; did you mean ")?;
+                    for suggestion in rest {
+                        write!(f, "`{suggestion}`, ")?;
+                    }
+                    if !rest.is_empty() {
+                        write!(f, "or ")?;
+                    }
+                    write!(f, "`{last}`?")?;
+                }
+                Ok(())
+            }
+            KdlErrorKind::UnknownVariant {
+                shape,
+                name,
+                expected,
+            } => {
+                write!(f, "unknown variant `{name}` of `{shape}`")?;
+                write_expected(f, expected)
+            }
+            KdlErrorKind::NoMatchingVariant { shape, rejections } => {
+                write!(f, "this doesn't fit any variant of `{shape}`")?;
+                for (variant, error) in rejections {
+                    write!(f, "\n  - `{variant}`: {}", error.kind())?;
+                }
+                Ok(())
+            }
+            KdlErrorKind::UnknownDiscriminant {
+                shape,
+                value,
+                expected,
+            } => {
+                write!(f, "unknown code `{value}` for `{shape}`")?;
+                write_expected(f, expected)
+            }
+            KdlErrorKind::MissingNodes(expected) => write!(f, "missing {}", expected.join(", ")),
+            KdlErrorKind::UnexpectedNode { expected, found } => {
+                write!(f, "expected a `{expected}` node, but found `{found}`")
+            }
+            KdlErrorKind::TooDeep(limit) => {
+                write!(f, "the document is nested more than {limit} levels deep")
+            }
+            KdlErrorKind::UnsupportedRoot(shape) => write!(
+                f,
+                "a KDL document can't be deserialized into `{shape}`, only into a struct or a list"
+            ),
+            KdlErrorKind::Parse(kdl_error) => write!(f, "{kdl_error}"),
+            KdlErrorKind::Reflect(reflect_error) => write!(f, "{reflect_error}"),
+        }
+    }
+}
+
+/// Describes what a node holding a value of `shape` should look like, like "have a single `u16` argument".
+fn write_node_layout(f: &mut fmt::Formatter<'_>, shape: &'static Shape) -> fmt::Result {
+    match (&shape.ty, &shape.def) {
+        (_, Def::Map(_)) => write!(
+            f,
+            "have a block of child nodes for the entries of `{shape}`"
+        ),
+        (Type::User(UserType::Enum(_)), _) if crate::has_data_variants(shape) => write!(
+            f,
+            "have a single child node named after a variant of `{shape}`"
+        ),
+        _ => match crate::positional_len(shape) {
+            Some(len) => {
+                let plural = if len == 1 { "" } else { "s" };
+                write!(f, "have just {len} argument{plural} for `{shape}`")
+            }
+            None => write!(f, "have a single `{shape}` argument"),
+        },
+    }
+}
+
+/// Writes the list of values that would have been accepted, like ", expected one of `a`, `b`, or `c`".
+fn write_expected(f: &mut fmt::Formatter<'_>, expected: &[impl Display]) -> fmt::Result {
+    if let [rest @ .., last] = expected {
+        write!(f, ", expected ")?;
+        if !rest.is_empty() {
+            write!(f, "one of ")?;
+        }
+        for item in rest {
+            write!(f, "`{item}`, ")?;
+        }
+        if !rest.is_empty() {
+            write!(f, "or ")?;
+        }
+        write!(f, "`{last}`")?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "miette")]
+impl KdlErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            KdlErrorKind::Mismatch { .. } => "facet_kdl::mismatch",
+            KdlErrorKind::UnexpectedArgument { .. } => "facet_kdl::unexpected_argument",
+            KdlErrorKind::Arity { .. } => "facet_kdl::arity",
+            KdlErrorKind::UnknownField { .. } => "facet_kdl::unknown_field",
+            KdlErrorKind::UnknownVariant { .. } => "facet_kdl::unknown_variant",
+            KdlErrorKind::NoMatchingVariant { .. } => "facet_kdl::no_matching_variant",
+            KdlErrorKind::UnknownDiscriminant { .. } => "facet_kdl::unknown_discriminant",
+            KdlErrorKind::MissingNodes(_) => "facet_kdl::missing_nodes",
+            KdlErrorKind::UnexpectedNode { .. } => "facet_kdl::unexpected_node",
+            KdlErrorKind::TooDeep(_) => "facet_kdl::too_deep",
+            KdlErrorKind::UnsupportedRoot(_) => "facet_kdl::unsupported_root",
+            KdlErrorKind::Parse(_) => "facet_kdl::parse",
+            KdlErrorKind::Reflect(_) => "facet_kdl::reflect",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            KdlErrorKind::Mismatch { .. } => "this doesn't fit",
+            KdlErrorKind::UnexpectedArgument { .. } => "unexpected argument",
+            KdlErrorKind::Arity { .. } => "wrong number of arguments",
+            KdlErrorKind::UnknownField { .. } => "unknown name",
+            KdlErrorKind::UnknownVariant { .. } => "unknown variant",
+            KdlErrorKind::NoMatchingVariant { .. } => "fits no variant",
+            KdlErrorKind::UnknownDiscriminant { .. } => "unknown code",
+            KdlErrorKind::MissingNodes(_) => "in here",
+            KdlErrorKind::UnexpectedNode { .. } => "unexpected node",
+            KdlErrorKind::TooDeep(_) => "too deeply nested",
+            KdlErrorKind::UnsupportedRoot(_) => "this document",
+            KdlErrorKind::Parse(_) => "invalid KDL",
+            KdlErrorKind::Reflect(_) => "this value",
+        }
+    }
+
+    fn help(&self) -> Option<&'static str> {
+        match self {
+            KdlErrorKind::Mismatch {
+                found: Found::Value(KdlValue::Null),
+                ..
+            } => Some("only `Option` fields can be `#null`"),
+            KdlErrorKind::Mismatch { .. } => Some(
+                "check that the layout of the document matches the type it's being deserialized into",
+            ),
+            KdlErrorKind::UnexpectedArgument { .. } => Some("remove the extra argument"),
+            KdlErrorKind::Arity { .. } => None,
+            KdlErrorKind::UnknownField { .. } => {
+                Some("check the spelling against the fields of the type being deserialized")
+            }
+            KdlErrorKind::UnknownVariant { .. } => None,
+            KdlErrorKind::NoMatchingVariant { .. } => {
+                Some("change this to fit one of the variants, each listed with why it didn't fit")
+            }
+            KdlErrorKind::UnknownDiscriminant { .. } => None,
+            KdlErrorKind::MissingNodes(_) => {
+                Some("add the missing entries and nodes to the document")
+            }
+            KdlErrorKind::UnexpectedNode { .. } => {
+                Some("every node in this list should have the same name")
+            }
+            KdlErrorKind::TooDeep(_) => Some("flatten the document, or split it into several"),
+            KdlErrorKind::UnsupportedRoot(_) => {
+                Some("wrap the type in a struct with a field for each top-level node")
+            }
+            KdlErrorKind::Parse(_) => None,
+            KdlErrorKind::Reflect(_) => {
+                Some("check that this value has the right type for the field it's being written to")
+            }
+        }
+    }
+}
+
+impl From<KdlParseError> for KdlErrorKind {
+    fn from(value: KdlParseError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+impl From<ReflectError> for KdlErrorKind {
+    fn from(value: ReflectError) -> Self {
+        Self::Reflect(value)
+    }
+}
+
+/// Where in a Rust value some KDL was meant to end up.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Target {
+    /// The KDL construct that was expected to hold the value.
+    pub role: Role,
+    /// The type containing the field being filled.
+    pub parent: &'static Shape,
+    /// The name of the field being filled.
+    pub field: String,
+}
+
+/// The KDL constructs that can hold a Rust value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Role {
+    /// A positional entry on a node, like the `"web"` in `process "web"`.
+    Argument(usize),
+    /// A named entry on a node, like the `port=80` in `listen port=80`.
+    Property,
+    /// A child node, like `version "1.0.0"` inside of `meta { ... }`.
+    Child,
+}
+
+/// A short description of the KDL that was found where a value was expected.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Found {
+    /// A single argument or property value.
+    Value(KdlValue),
+    /// A whole node.
+    Node {
+        /// How many arguments the node had.
+        arguments: usize,
+        /// How many properties the node had.
+        properties: usize,
+        /// How many child nodes the node had.
+        children: usize,
+    },
+}
+
+impl Found {
+    pub(crate) fn node(node: &KdlNode) -> Self {
+        let properties = node
+            .entries()
+            .iter()
+            .filter(|entry| entry.name().is_some())
+            .count();
+        Found::Node {
+            arguments: node.entries().len() - properties,
+            properties,
+            children: node.children().map_or(0, |children| children.nodes().len()),
+        }
+    }
+}
+
+impl Display for Found {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Found::Value(value @ KdlValue::String(_)) => write!(f, "string `{value}`"),
+            Found::Value(value @ KdlValue::Integer(_)) => write!(f, "integer `{value}`"),
+            Found::Value(value @ KdlValue::Float(_)) => write!(f, "float `{value}`"),
+            Found::Value(value @ KdlValue::Bool(_)) => write!(f, "boolean `{value}`"),
+            Found::Value(value) => write!(f, "`{value}`"),
+            Found::Node {
+                arguments,
+                properties,
+                children,
+            } => {
+                let parts: Vec<_> = [
+                    (*arguments, "argument", "arguments"),
+                    (*properties, "property", "properties"),
+                    (*children, "child", "children"),
+                ]
+                .into_iter()
+                .filter(|&(count, _, _)| count > 0)
+                .map(|(count, one, many)| {
+                    format!("{count} {}", if count == 1 { one } else { many })
+                })
+                .collect();
+
+                match parts.as_slice() {
+                    [] => write!(f, "an empty node"),
+                    [only] => write!(f, "node with {only}"),
+                    [rest @ .., last] => write!(f, "node with {} and {last}", rest.join(", ")),
+                }
+            }
+        }
+    }
+}
+
+/// A region of the KDL source text, as a byte range plus the (1-based) line and column it starts at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    offset: usize,
+    len: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Span {
+    /// Works out the line and column of the byte range `offset..offset + len` in `source_code`.
+    pub(crate) fn new(source_code: &str, offset: usize, len: usize) -> Self {
+        let offset = offset.min(source_code.len());
+        let before = source_code.get(..offset).unwrap_or_default();
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        Self {
+            offset,
+            len,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// The byte offset of the start of the span.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The length of the span in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the span covers no text at all.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The byte range covered by the span.
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.offset..self.offset + self.len
+    }
+
+    /// The line the span starts on, counting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column the span starts at, counting characters from 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Writes the source line this span starts on, with carets underneath the spanned text.
+    fn write_snippet(&self, f: &mut fmt::Formatter<'_>, source_code: &str) -> fmt::Result {
+        let Some(before) = source_code.get(..self.offset) else {
+            return Ok(());
+        };
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        let line = source_code[line_start..].lines().next().unwrap_or_default();
+
+        // NOTE: Reuse any tabs from the source line so that the carets still line up with the text above them
+        let padding: String = before[line_start..]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let spanned = source_code
+            .get(self.offset..)
+            .unwrap_or_default()
+            .char_indices()
+            .take_while(|&(i, c)| i < self.len && c != '\n')
+            .count();
+        let carets = "^".repeat(spanned.max(1));
+
+        let line_number = self.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        write!(
+            f,
+            "\n{gutter} |\n{line_number} | {line}\n{gutter} | {padding}{carets}"
+        )
+    }
+}
+
+/// Breadcrumbs leading to a value in a KDL document, like `process[1].env.PORT`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Path(Vec<PathSegment>);
+
+impl Path {
+    /// The steps along the path, outermost first.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// How many steps there are along the path.
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub(crate) fn push(&mut self, segment: PathSegment) {
+        self.0.push(segment);
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.0.pop();
+    }
+}
+
+impl FromIterator<PathSegment> for Path {
+    fn from_iter<I: IntoIterator<Item = PathSegment>>(segments: I) -> Self {
+        Self(segments.into_iter().collect())
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) | PathSegment::Key(name) => {
+                    if i > 0 {
+                        write!(f, ".")?;
+                    }
+                    write!(f, "{name}")?;
+                }
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One step along a [`Path`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PathSegment {
+    /// A struct field, filled from a node, property or argument.
+    Field(String),
+    /// An element of a list.
+    Index(usize),
+    /// An entry in a map.
+    Key(String),
+}
@@ -3,17 +3,22 @@
 
 // cf. facet-toml/facet-json for examples
 
-mod serialize;
-pub use serialize::{KdlSerializeError, KdlSerializer, to_string};
+mod error;
+pub use error::{Found, KdlError, KdlErrorKind, Path, PathSegment, Role, Span, Target};
 
-use std::{
-    error::Error,
-    fmt::{self, Display},
+mod serialize;
+mod suggest;
+pub use serialize::{
+    KdlSerializeError, KdlSerializeErrorKind, KdlSerializer, to_string, to_string_nodes,
 };
 
-use facet_core::{Def, Facet, Type, UserType};
-use facet_reflect::{Partial, ReflectError};
-use kdl::{KdlDocument, KdlError as KdlParseError};
+use std::{collections::HashMap, sync::Arc};
+
+use facet_core::{
+    Def, Facet, Field, FieldAttribute, FieldFlags, Shape, StructKind, Type, UserType, Variant,
+};
+use facet_reflect::Partial;
+use kdl::{KdlDocument, KdlEntry, KdlIdentifier, KdlNode};
 
 // QUESTION: Any interest in making something a bit like `strum` with `facet`? Always nice to have an easy way to get
 // the names of enum variants as strings!
@@ -27,114 +32,301 @@ use kdl::{KdlDocument, KdlError as KdlParseError};
 // optimisations, like flattening this recursive structure into something more iterative / imparative (as in
 // `facet-json`) or parsing things more incrementally by using `KdlNode::parse()` or `KdlEntry::parse`.
 
-// TODO: Need to actually add some shared information here so it's not just a useless wrapper...
+/// Parts of the KDL AST that remember where they came from in the source text.
+trait Spanned {
+    fn byte_range(&self) -> (usize, usize);
+}
 
-/// Error type for KDL deserialization.
-#[derive(Debug)]
-pub struct KdlError {
-    kind: KdlErrorKind,
+macro_rules! impl_spanned {
+    ($($ty:ty),*) => {$(
+        impl Spanned for $ty {
+            fn byte_range(&self) -> (usize, usize) {
+                let span = self.span();
+                (span.offset(), span.len())
+            }
+        }
+    )*};
 }
 
-impl Display for KdlError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
-        let kind = &self.kind;
-        write!(f, "{kind}")
-    }
+impl_spanned!(KdlDocument, KdlNode, KdlEntry, KdlIdentifier);
+
+/// Walks a parsed KDL document alongside the `Partial` being built from it.
+struct KdlDeserializer<'input> {
+    /// The KDL text being deserialized.
+    kdl: &'input str,
+    /// Shared copy of `kdl`, so that errors can show snippets of it after the input is gone.
+    source_code: Arc<String>,
+    /// Breadcrumbs leading to the value currently being deserialized.
+    path: Path,
+    /// The field whose value is currently being deserialized, whose attributes decide how enums in it are tagged.
+    field: Option<&'static Field>,
+    /// Every error found so far, when collecting all of them rather than stopping at the first.
+    errors: Option<Vec<KdlError>>,
+    /// The variant of an untagged enum that fit the node or entry starting at some offset, keyed by that offset and
+    /// the enum's shape.
+    untagged_variants: HashMap<(usize, *const Shape), &'static Variant>,
+    /// The span of the property that picked the variant of the tagged enum currently being deserialized, which isn't
+    /// one of the variant's fields.
+    tag_entry: Option<(usize, usize)>,
 }
-impl Error for KdlError {}
 
-// FIXME: Replace this with a proper constructor once there is other information to put into `KdlError`!
-impl<K: Into<KdlErrorKind>> From<K> for KdlError {
-    fn from(value: K) -> Self {
-        let kind = value.into();
-        KdlError { kind }
+type Result<T> = std::result::Result<T, KdlError>;
+
+/// How many levels of nodes and values deep a document can be deserialized, which keeps recursive types from
+/// overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+impl<'input, 'facet> KdlDeserializer<'input> {
+    fn new(kdl: &'input str) -> Self {
+        Self {
+            kdl,
+            source_code: Arc::new(kdl.to_owned()),
+            path: Path::default(),
+            field: None,
+            errors: None,
+            untagged_variants: HashMap::new(),
+            tag_entry: None,
+        }
     }
-}
 
-#[derive(Debug)]
-enum KdlErrorKind {
-    InvalidDocumentShape(&'static Def),
-    MissingNodes(Vec<String>),
-    Parse(KdlParseError),
-    Reflect(ReflectError),
-}
+    /// The entries of `node`, without the property that picked the variant of the enum being deserialized.
+    fn entries<'a>(&self, node: &'a KdlNode) -> Vec<&'a KdlEntry> {
+        node.entries()
+            .iter()
+            .filter(|entry| Some(entry.byte_range()) != self.tag_entry)
+            .collect()
+    }
 
-impl Display for KdlErrorKind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            KdlErrorKind::InvalidDocumentShape(def) => {
-                write!(f, "invalid shape {def:#?} — needed... TODO")
-            }
-            KdlErrorKind::MissingNodes(expected) => write!(f, "failed to find node {expected:?}"),
-            KdlErrorKind::Parse(kdl_error) => write!(f, "{kdl_error}"),
-            KdlErrorKind::Reflect(reflect_error) => write!(f, "{reflect_error}"),
+    /// Whether anything has gone wrong yet while collecting every error.
+    fn has_failed(&self) -> bool {
+        self.errors
+            .as_ref()
+            .is_some_and(|errors| !errors.is_empty())
+    }
+
+    /// Deserializes a single entry or child node with `f`.
+    ///
+    /// When collecting every error, a failure is recorded instead of returned, so that the caller can skip to the next
+    /// entry or node. After something has failed, `wip` may have been left half-way through building a value, so the
+    /// rest of the document is checked against fresh scratch values of the same `shape` instead.
+    fn recover(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        shape: &'static Shape,
+        f: impl FnOnce(&mut Self, &mut Partial<'facet>) -> Result<()>,
+    ) -> Result<()> {
+        if self.errors.is_none() {
+            return f(self, wip);
+        }
+
+        let result = if self.has_failed() {
+            let variant = wip.selected_variant();
+            Partial::alloc_shape(shape)
+                .map_err(KdlError::from)
+                .and_then(|mut scratch| {
+                    // NOTE: The fields of an enum can only be filled in once the same variant has been selected
+                    if let Some(variant) = variant {
+                        scratch.select_variant_named(variant.name)?;
+                    }
+                    f(self, &mut scratch)
+                })
+        } else {
+            f(self, wip)
+        };
+
+        if let (Err(error), Some(errors)) = (result, &mut self.errors) {
+            errors.push(error);
         }
+        Ok(())
     }
-}
 
-impl From<KdlParseError> for KdlErrorKind {
-    fn from(value: KdlParseError) -> Self {
-        Self::Parse(value)
+    /// Finishes the value `wip` is currently building, unless an earlier failure means it will never be finished.
+    fn end(&self, wip: &mut Partial<'facet>) -> Result<()> {
+        if !self.has_failed() {
+            wip.end()?;
+        }
+        Ok(())
     }
-}
 
-impl From<ReflectError> for KdlErrorKind {
-    fn from(value: ReflectError) -> Self {
-        Self::Reflect(value)
+    /// Points any error in `result` at `item` and the current path, unless that error already points somewhere more
+    /// precise.
+    fn locate<T>(&self, result: Result<T>, item: &impl Spanned) -> Result<T> {
+        result.map_err(|error| {
+            let (offset, len) = item.byte_range();
+            error
+                .located(Span::new(self.kdl, offset, len), &self.source_code)
+                .at_path(&self.path)
+        })
     }
-}
 
-// FIXME: I'm not sure what to name this...
-#[allow(dead_code)]
-struct KdlDeserializer<'input> {
-    // FIXME: Also no clue what fields it should have, if it should exist at all...
-    kdl: &'input str,
-}
+    /// Runs `f` with `segment` added to the current path, pointing any error it returns at `item`.
+    ///
+    /// Every level of nesting in the document goes through here, so this is also where overly deep documents are
+    /// turned away, before they can overflow the stack.
+    fn visit<T>(
+        &mut self,
+        segment: PathSegment,
+        item: &impl Spanned,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        if self.path.len() >= MAX_DEPTH {
+            let error = KdlError::from(KdlErrorKind::TooDeep(MAX_DEPTH));
+            return self.locate(Err(error), item);
+        }
+        self.path.push(segment);
+        let field = self.field;
+        let result = f(self);
+        let result = self.locate(result, item);
+        self.field = field;
+        self.path.pop();
+        result
+    }
 
-type Result<T> = std::result::Result<T, KdlError>;
+    /// Begins filling in the field at `index` of `fields`, which `wip` is building.
+    ///
+    /// The field's attributes then apply to any enums in it until the current [`Self::visit`] is over.
+    fn begin_field(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        fields: &'static [Field],
+        index: usize,
+    ) -> Result<()> {
+        wip.begin_nth_field(index)?;
+        self.field = fields.get(index);
+        Ok(())
+    }
+
+    /// Deserializes the value of `entry`.
+    ///
+    /// In `#[facet(annotated)]` fields, the entry's type annotation picks the variant of an enum, which holds the
+    /// value, like `timeout (seconds)30`.
+    fn deserialize_entry(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        entry: &KdlEntry,
+        target: &Target,
+    ) -> Result<()> {
+        if let Def::Option(_) = wip.shape().def {
+            return self.deserialize_option(wip, entry.value(), |this, wip| {
+                this.deserialize_entry(wip, entry, target)
+            });
+        }
+
+        let is_enum = matches!(wip.shape().ty, Type::User(UserType::Enum(_)));
+        if is_enum && tagging(self.field) == Tagging::Untagged {
+            return self.deserialize_untagged(wip, entry, |this, wip, variant| {
+                this.fill_variant_value(wip, variant, entry.value(), target)
+            });
+        }
+        let annotation = entry
+            .ty()
+            .filter(|_| is_enum && tagging(self.field) == Tagging::Annotation);
+        let Some(annotation) = annotation else {
+            return self.deserialize_value(wip, entry.value(), target);
+        };
+
+        let variant = self.select_variant(wip, annotation.value(), annotation)?;
+        self.fill_variant_value(wip, variant, entry.value(), target)
+    }
+
+    /// Deserializes `value` into `variant`, which has been selected in the enum `wip` is building and must hold a
+    /// single value.
+    fn fill_variant_value(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        variant: &'static Variant,
+        value: &kdl::KdlValue,
+        target: &Target,
+    ) -> Result<()> {
+        match variant.data.fields {
+            [_] if !matches!(variant.data.kind, StructKind::Struct) => {
+                wip.begin_nth_field(0)?;
+                self.deserialize_value(wip, value, target)?;
+                self.end(wip)
+            }
+            _ => Err(mismatch(target, wip.shape(), Found::Value(value.clone()))),
+        }
+    }
+
+    /// Deserializes the first variant of the enum `wip` is building that fits, trying each of them in order with
+    /// `fill`.
+    ///
+    /// Every attempt is made on a scratch value of its own, which is thrown away, so that a variant that doesn't fit
+    /// leaves nothing behind. Once a variant fits, `fill` runs again on `wip` itself. The variant that fit `item` is
+    /// remembered, so that untagged enums nested inside of it are only tried once, rather than again every time an
+    /// enum around them is.
+    fn deserialize_untagged(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        item: &impl Spanned,
+        fill: impl Fn(&mut Self, &mut Partial<'facet>, &'static Variant) -> Result<()>,
+    ) -> Result<()> {
+        let shape = wip.shape();
+        let key = (item.byte_range().0, shape as *const Shape);
+        if let Some(&variant) = self.untagged_variants.get(&key) {
+            wip.select_variant_named(variant.name)?;
+            return fill(self, wip, variant);
+        }
+
+        let mut rejections = Vec::new();
+        for variant in enum_variants(shape) {
+            log::trace!("Trying variant `{}` of `{shape}`", variant.name);
+
+            // NOTE: Attempts are expected to fail, so their errors are returned rather than collected
+            let errors = self.errors.take();
+            let attempt = Partial::alloc_shape(shape)
+                .map_err(KdlError::from)
+                .and_then(|mut scratch| {
+                    scratch.select_variant_named(variant.name)?;
+                    fill(self, &mut scratch, variant)?;
+                    scratch.build()?;
+                    Ok(())
+                });
+            self.errors = errors;
+
+            match attempt {
+                Ok(()) => {
+                    self.untagged_variants.insert(key, variant);
+                    wip.select_variant_named(variant.name)?;
+                    return fill(self, wip, variant);
+                }
+                Err(error) => rejections.push((variant.name, error)),
+            }
+        }
+
+        Err(KdlErrorKind::NoMatchingVariant { shape, rejections }.into())
+    }
 
-impl<'input, 'facet> KdlDeserializer<'input> {
     fn deserialize_value(
         &mut self,
         wip: &mut Partial<'facet>,
         value: &kdl::KdlValue,
+        target: &Target,
     ) -> Result<()> {
         log::trace!("Deserializing value: {:?}", value);
         log::trace!("Current shape: {:?}", wip.shape());
 
+        if let Def::Option(_) = wip.shape().def {
+            return self.deserialize_option(wip, value, |this, wip| {
+                this.deserialize_value(wip, value, target)
+            });
+        }
+        if let Type::User(UserType::Enum(_)) = wip.shape().ty {
+            return self.deserialize_unit_variant(wip, value, target);
+        }
+
         // Check if it's a scalar or undefined type
         match &wip.shape().def {
             facet_core::Def::Scalar => {
                 // For scalar types, we need to handle them directly
-                self.deserialize_scalar_value(wip, value)?;
+                self.deserialize_scalar_value(wip, value, target)?;
             }
             facet_core::Def::Undefined => {
                 // Undefined types like String need special handling
                 if let kdl::KdlValue::String(s) = value {
                     log::trace!("Handling undefined type with value: {}", s);
 
-                    // Check if this is a String type and try multiple approaches
-                    if wip.shape().type_identifier == "String" {
-                        // Try set_from_function to directly write to memory
-                        log::trace!("Trying set_from_function for String");
-                        let string_value = s.clone();
-                        if wip
-                            .set_from_function(move |ptr| {
-                                unsafe {
-                                    let string_ptr = ptr.as_mut_byte_ptr() as *mut String;
-                                    core::ptr::write(string_ptr, string_value);
-                                }
-                                Ok(())
-                            })
-                            .is_ok()
-                        {
-                            log::trace!("set_from_function succeeded");
-                            return Ok(());
-                        }
-                    }
-
-                    // Try different approaches for other undefined types
-                    // 1. Try direct set
+                    // 1. Try direct set, which checks that the type really is a `String`
                     if wip.set(s.clone()).is_ok() {
                         log::trace!("Direct set succeeded");
                         return Ok(());
@@ -148,32 +340,81 @@ impl<'input, 'facet> KdlDeserializer<'input> {
                     }
 
                     log::error!("Failed to set undefined type value: {}", s);
-                    return Err(KdlError::from(KdlErrorKind::InvalidDocumentShape(
-                        &wip.shape().def,
-                    )));
+                    return Err(mismatch(target, wip.shape(), Found::Value(value.clone())));
                 } else {
                     log::warn!("Non-string value for undefined type: {:?}", value);
-                    return Err(KdlError::from(KdlErrorKind::InvalidDocumentShape(
-                        &wip.shape().def,
-                    )));
+                    return Err(mismatch(target, wip.shape(), Found::Value(value.clone())));
                 }
             }
             _ => {
                 // For non-scalar types, we might need to handle them differently
                 log::warn!("Non-scalar type encountered: {:?}", wip.shape().def);
-                return Err(KdlError::from(KdlErrorKind::InvalidDocumentShape(
-                    &wip.shape().def,
-                )));
+                return Err(mismatch(target, wip.shape(), Found::Value(value.clone())));
             }
         }
 
         Ok(())
     }
 
+    /// Deserializes `value` into the `Option` that `wip` is building, which is `None` for `#null` and otherwise `Some`
+    /// of whatever `f` fills in.
+    fn deserialize_option(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        value: &kdl::KdlValue,
+        f: impl FnOnce(&mut Self, &mut Partial<'facet>) -> Result<()>,
+    ) -> Result<()> {
+        if let kdl::KdlValue::Null = value {
+            wip.set_default()?;
+            return Ok(());
+        }
+        wip.begin_some()?;
+        f(self, wip)?;
+        self.end(wip)
+    }
+
+    /// Deserializes a `value` naming one of the unit variants of the enum `wip` is building, like `"fast"` or
+    /// `level=warn`, or giving its discriminant, like `priority 3`.
+    fn deserialize_unit_variant(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        value: &kdl::KdlValue,
+        target: &Target,
+    ) -> Result<()> {
+        let shape = wip.shape();
+        let unit_variants = || {
+            enum_variants(shape)
+                .iter()
+                .filter(|variant| variant.data.fields.is_empty())
+        };
+        let variant = match value {
+            kdl::KdlValue::String(name) => unit_variants()
+                .find(|variant| variant.name == name)
+                .ok_or_else(|| KdlErrorKind::UnknownVariant {
+                    shape,
+                    name: name.clone(),
+                    expected: unit_variants().map(|variant| variant.name).collect(),
+                })?,
+            kdl::KdlValue::Integer(code) if accepts_codes(shape, self.field) => unit_variants()
+                .find(|variant| variant.discriminant.map(i128::from) == Some(*code))
+                .ok_or_else(|| KdlErrorKind::UnknownDiscriminant {
+                    shape,
+                    value: *code,
+                    expected: unit_variants()
+                        .filter_map(|variant| variant.discriminant)
+                        .collect(),
+                })?,
+            _ => return Err(mismatch(target, shape, Found::Value(value.clone()))),
+        };
+        wip.select_variant_named(variant.name)?;
+        Ok(())
+    }
+
     fn deserialize_scalar_value(
         &mut self,
         wip: &mut Partial<'facet>,
         value: &kdl::KdlValue,
+        target: &Target,
     ) -> Result<()> {
         log::trace!("Deserializing scalar value: {:?}", value);
 
@@ -181,14 +422,9 @@ impl<'input, 'facet> KdlDeserializer<'input> {
         use std::borrow::Cow;
 
         // Get the scalar type from the shape
-        let scalar_type = ScalarType::try_from_shape(wip.shape()).ok_or_else(|| {
-            KdlError::from(KdlErrorKind::Reflect(
-                facet_reflect::ReflectError::OperationFailed {
-                    operation: "Not a scalar type",
-                    shape: wip.shape(),
-                },
-            ))
-        })?;
+        let shape = wip.shape();
+        let scalar_type = ScalarType::try_from_shape(shape)
+            .ok_or_else(|| mismatch(target, shape, Found::Value(value.clone())))?;
 
         match (scalar_type, value) {
             // String types
@@ -273,28 +509,22 @@ impl<'input, 'facet> KdlDeserializer<'input> {
             }
 
             // Char type
-            (ScalarType::Char, kdl::KdlValue::String(s)) if s.len() == 1 => {
-                wip.set(s.chars().next().unwrap())?;
-            }
-
-            // Handle null for any type (will use default)
-            (_, kdl::KdlValue::Null) => {
-                wip.set_default()?;
-            }
+            (ScalarType::Char, kdl::KdlValue::String(s)) => match s.parse::<char>() {
+                Ok(c) => {
+                    wip.set(c)?;
+                }
+                Err(_) => return Err(mismatch(target, shape, Found::Value(value.clone()))),
+            },
 
             // For types that might implement FromStr
             (_, kdl::KdlValue::String(s)) => {
                 // Try to parse from string as a fallback
-                wip.parse_from_str(s)?;
+                wip.parse_from_str(s)
+                    .map_err(|_| mismatch(target, shape, Found::Value(value.clone())))?;
             }
 
             _ => {
-                return Err(KdlError::from(KdlErrorKind::Reflect(
-                    facet_reflect::ReflectError::OperationFailed {
-                        operation: "Type mismatch in scalar deserialization",
-                        shape: wip.shape(),
-                    },
-                )));
+                return Err(mismatch(target, shape, Found::Value(value.clone())));
             }
         }
 
@@ -305,37 +535,173 @@ impl<'input, 'facet> KdlDeserializer<'input> {
         &mut self,
         wip: &mut Partial<'facet>,
         name: &str,
-        value: &kdl::KdlValue,
+        entry: &KdlEntry,
     ) -> Result<()> {
-        log::trace!("Deserializing property '{}': {:?}", name, value);
-        eprintln!("DEBUG: deserialize_property '{}': {:?}", name, value);
-        eprintln!("  Current shape: {}", wip.shape().type_identifier);
-
-        // Begin the field by name
-        wip.begin_field(name)?;
-        eprintln!(
-            "  After begin_field, shape: {}",
-            wip.shape().type_identifier
-        );
+        log::trace!("Deserializing property '{}': {:?}", name, entry.value());
+
+        let target = Target {
+            role: Role::Property,
+            parent: wip.shape(),
+            field: name.to_owned(),
+        };
+        self.visit(PathSegment::Field(name.to_owned()), entry, |this| {
+            let fields = fields_of(wip);
+            let field_index = field_named(target.parent, fields, name, Role::Property)?;
+            this.begin_field(wip, fields, field_index)?;
+            log::trace!("After begin_field, shape: {}", wip.shape().type_identifier);
+            this.deserialize_entry(wip, entry, &target)?;
+            this.end(wip)
+        })
+    }
+
+    fn deserialize_argument(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        arg_index: usize,
+        entry: &KdlEntry,
+    ) -> Result<()> {
+        log::trace!("Deserializing argument #{}: {:?}", arg_index, entry.value());
+
+        let fields = fields_of(wip);
+        let Some(field_index) = argument_field(fields, arg_index) else {
+            let error = KdlError::from(KdlErrorKind::UnexpectedArgument {
+                parent: wip.shape(),
+                index: arg_index,
+                found: Found::Value(entry.value().clone()),
+            });
+            return self.locate(Err(error), entry);
+        };
+
+        let name = fields[field_index].name;
+        let target = Target {
+            role: Role::Argument(arg_index),
+            parent: wip.shape(),
+            field: name.to_owned(),
+        };
+        self.visit(PathSegment::Field(name.to_owned()), entry, |this| {
+            this.begin_field(wip, fields, field_index)?;
+            this.deserialize_entry(wip, entry, &target)?;
+            this.end(wip)
+        })
+    }
 
-        // Check if we're dealing with a String type that needs parse_from_str
-        // String types are Scalar with is_from_str() true
-        if wip.shape().type_identifier == "String" && wip.shape().is_from_str() {
-            if let kdl::KdlValue::String(s) = value {
-                eprintln!("  Using parse_from_str for String");
-                wip.parse_from_str(s)?;
-                // parse_from_str completes the field, so we don't need to call end()
-                return Ok(());
+    /// Deserializes `entries` as the elements of the `#[facet(arguments)]` field at `field_index`, where the first of
+    /// them is argument #`first_index` of `node`.
+    fn deserialize_arguments(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        field_index: usize,
+        first_index: usize,
+        entries: &[&KdlEntry],
+        node: &KdlNode,
+    ) -> Result<()> {
+        let parent = wip.shape();
+        let fields = fields_of(wip);
+        let name = fields[field_index].name;
+        log::trace!("Deserializing {} arguments into `{name}`", entries.len());
+
+        self.visit(PathSegment::Field(name.to_owned()), node, |this| {
+            this.begin_field(wip, fields, field_index)?;
+            let collection = wip.shape();
+            match &collection.def {
+                Def::List(_) => {
+                    wip.begin_list()?;
+                }
+                Def::Set(_) => {
+                    wip.begin_set()?;
+                }
+                Def::Array(array_def) if array_def.n != entries.len() => {
+                    return Err(KdlErrorKind::Arity {
+                        parent,
+                        field: name.to_owned(),
+                        expected: array_def.n,
+                        found: entries.len(),
+                    }
+                    .into());
+                }
+                Def::Array(_) => {}
+                _ => {
+                    let target = Target {
+                        role: Role::Argument(first_index),
+                        parent,
+                        field: name.to_owned(),
+                    };
+                    let found = match entries.first() {
+                        Some(entry) => Found::Value(entry.value().clone()),
+                        None => Found::node(node),
+                    };
+                    return Err(mismatch(&target, collection, found));
+                }
             }
-        }
 
-        // For other types (including numbers), use the normal flow
-        // Note: deserialize_value with scalar types will call wip.set() which automatically completes the frame
-        self.deserialize_value(wip, value)?;
+            for (index, entry) in entries.iter().enumerate() {
+                let target = Target {
+                    role: Role::Argument(first_index + index),
+                    parent,
+                    field: name.to_owned(),
+                };
+                this.visit(PathSegment::Index(index), *entry, |this| {
+                    match &collection.def {
+                        Def::Set(_) => wip.begin_set_item()?,
+                        Def::Array(_) => wip.begin_nth_element(index)?,
+                        _ => wip.begin_list_item()?,
+                    };
+                    this.deserialize_entry(wip, entry, &target)?;
+                    this.end(wip)
+                })?;
+            }
 
-        // Don't call end() here - wip.set() in scalar types already completes the frame
+            this.end(wip)
+        })
+    }
 
-        Ok(())
+    /// Deserializes `entries` as the entries of the `#[facet(properties)]` map field at `field_index`, keyed by their
+    /// names.
+    fn deserialize_extra_properties(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        field_index: usize,
+        entries: &[&KdlEntry],
+        node: &KdlNode,
+    ) -> Result<()> {
+        let parent = wip.shape();
+        let fields = fields_of(wip);
+        let name = fields[field_index].name;
+        log::trace!("Deserializing {} properties into `{name}`", entries.len());
+
+        self.visit(PathSegment::Field(name.to_owned()), node, |this| {
+            this.begin_field(wip, fields, field_index)?;
+            let map = wip.shape();
+            if !matches!(map.def, Def::Map(_)) {
+                let target = Target {
+                    role: Role::Property,
+                    parent,
+                    field: name.to_owned(),
+                };
+                return Err(mismatch(&target, map, Found::node(node)));
+            }
+            wip.begin_map()?;
+
+            for entry in entries {
+                let Some(key) = entry.name().map(|key| key.value()) else {
+                    continue;
+                };
+                let target = Target {
+                    role: Role::Property,
+                    parent,
+                    field: key.to_owned(),
+                };
+                this.recover(wip, map, |this, wip| {
+                    this.visit(PathSegment::Key(key.to_owned()), *entry, |this| {
+                        this.deserialize_map_entry(wip, key, &target, |this, wip| {
+                            this.deserialize_entry(wip, entry, &target)
+                        })
+                    })
+                })?;
+            }
+
+            this.end(wip)
+        })
     }
 
     fn deserialize_children(
@@ -344,53 +710,138 @@ impl<'input, 'facet> KdlDeserializer<'input> {
         children: &kdl::KdlDocument,
     ) -> Result<()> {
         log::trace!("Deserializing children nodes");
-        eprintln!("DEBUG: deserialize_children");
-        eprintln!(
-            "  Current shape in children: {}",
-            wip.shape().type_identifier
-        );
+        log::trace!("Current shape in children: {}", wip.shape().type_identifier);
 
-        // When we're here, we're already inside a field (e.g., "meta")
-        // So we just need to process the properties within
+        // Each child node fills in the field of the current struct with the same name
+        let shape = wip.shape();
+        let fields = fields_of(wip);
+        let mut filled_lists = Vec::new();
         for child_node in children.nodes() {
-            log::trace!("Processing child node: {:#?}", child_node.name());
-            eprintln!("  Processing child node: {}", child_node.name().value());
-
-            // Each node in children represents a property
-            for entry in child_node.entries() {
-                if let Some(name) = entry.name() {
-                    // Named property
-                    eprintln!("    Named entry: {}", name.value());
-                    self.deserialize_property(wip, name.value(), entry.value())?;
-                } else {
-                    // Positional property (using node name as field name)
-                    eprintln!("    Positional entry for: {}", child_node.name().value());
-                    self.deserialize_property(wip, child_node.name().value(), entry.value())?;
+            let name = child_node.name().value();
+            log::trace!("Processing child node: {name}");
+
+            // Every node that belongs to a list field is one of its elements, so they're all deserialized together
+            let list_field =
+                child_field(fields, name).filter(|&index| is_list(fields[index].shape()));
+            if let Some(field_index) = list_field {
+                if filled_lists.contains(&field_index) {
+                    continue;
                 }
+                filled_lists.push(field_index);
+
+                let nodes: Vec<&KdlNode> = children
+                    .nodes()
+                    .iter()
+                    .filter(|node| child_field(fields, node.name().value()) == Some(field_index))
+                    .collect();
+                let field_name = fields[field_index].name;
+                self.recover(wip, shape, |this, wip| {
+                    this.visit(
+                        PathSegment::Field(field_name.to_owned()),
+                        child_node,
+                        |this| {
+                            this.begin_field(wip, fields, field_index)?;
+                            this.deserialize_list_field(wip, &nodes)?;
+                            this.end(wip)
+                        },
+                    )
+                })?;
+                continue;
             }
 
-            // Process nested children if any
-            if let Some(nested_children) = child_node.children() {
-                eprintln!("  Node has nested children");
-                self.deserialize_children(wip, nested_children)?;
-            }
+            let target = Target {
+                role: Role::Child,
+                parent: shape,
+                field: name.to_owned(),
+            };
+            self.recover(wip, shape, |this, wip| {
+                this.visit(PathSegment::Field(name.to_owned()), child_node, |this| {
+                    let field_index = child_field(fields, name)
+                        .map_or_else(|| field_named(shape, fields, name, Role::Child), Ok)?;
+                    this.begin_field(wip, fields, field_index)?;
+                    log::trace!(
+                        "Node matched expected child; New def: {:#?}",
+                        wip.shape().def
+                    );
+                    this.deserialize_node(wip, child_node, &target)?;
+                    this.end(wip)
+                })
+            })?;
         }
 
         Ok(())
     }
 
-    fn from_str<T: Facet<'facet>>(kdl: &'input str) -> Result<T> {
+    /// Deserializes `nodes` as the elements of the list field `wip` is building.
+    fn deserialize_list_field(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        nodes: &[&KdlNode],
+    ) -> Result<()> {
+        // NOTE: Boxed slices are built as a list behind the pointer
+        let is_boxed = matches!(wip.shape().def, Def::Pointer(_));
+        if is_boxed {
+            wip.begin_smart_ptr()?;
+        }
+        self.deserialize_list_items(wip, nodes, None)?;
+        if is_boxed {
+            self.end(wip)?;
+        }
+        Ok(())
+    }
+
+    /// Deserializes each of `nodes` as the next element of the list `wip` is building.
+    ///
+    /// If `node_name` is given, every node must be called that.
+    fn deserialize_list_items(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        nodes: &[&KdlNode],
+        node_name: Option<&str>,
+    ) -> Result<()> {
+        let shape = wip.shape();
+        wip.begin_list()?;
+
+        for (index, node) in nodes.iter().enumerate() {
+            let name = node.name().value();
+            log::trace!("Processing list item #{index}: {name}");
+
+            let target = Target {
+                role: Role::Child,
+                parent: shape,
+                field: name.to_owned(),
+            };
+            self.recover(wip, shape, |this, wip| {
+                this.visit(PathSegment::Index(index), *node, |this| {
+                    if let Some(expected) = node_name.filter(|expected| *expected != name) {
+                        return Err(KdlErrorKind::UnexpectedNode {
+                            expected: expected.to_owned(),
+                            found: name.to_owned(),
+                        }
+                        .into());
+                    }
+
+                    // NOTE: After an earlier failure, `wip` is a fresh scratch list that hasn't been started yet
+                    if this.has_failed() {
+                        wip.begin_list()?;
+                    }
+                    wip.begin_list_item()?;
+                    this.deserialize_element(wip, node, &target)?;
+                    this.end(wip)
+                })
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn from_str<T: Facet<'facet>>(kdl: &'input str, node_name: Option<&str>) -> Result<T> {
         log::trace!("Entering `from_str` method");
 
-        // PERF: This definitely isn't zero-copy, so it might be worth seeing if that's something that can be added to
-        // `kdl-rs` at some point in the future?
-        // PERF: Would be be better / quicker if I did this parsing incrementally? Using information from the `Partial` to
-        // decide when to call `KdlNode::parse` and `KdlEntry::parse`? Probably would be if I'm only trying to parse
-        // some of the KDL text, but I'm not so sure otherwise? Will need benchmarking...
-        let document: KdlDocument = dbg!(kdl.parse()?);
-        log::trace!("KDL parsed");
+        let mut deserializer = Self::new(kdl);
+        let document = deserializer.parse()?;
 
-        let mut typed_partial = Partial::alloc::<T>().expect("failed to allocate");
+        let mut typed_partial = Partial::alloc::<T>()?;
         log::trace!(
             "Allocated WIP for type {}",
             typed_partial.inner_mut().shape()
@@ -398,7 +849,7 @@ impl<'input, 'facet> KdlDeserializer<'input> {
 
         {
             let wip = typed_partial.inner_mut();
-            Self { kdl }.deserialize_document(wip, document)?;
+            deserializer.deserialize_document(wip, document, node_name)?;
         }
 
         let boxed_value = typed_partial.build()?;
@@ -408,10 +859,81 @@ impl<'input, 'facet> KdlDeserializer<'input> {
         Ok(*boxed_value)
     }
 
+    fn from_str_all_errors<T: Facet<'facet>>(
+        kdl: &'input str,
+    ) -> std::result::Result<T, Vec<KdlError>> {
+        log::trace!("Entering `from_str_all_errors` method");
+
+        let mut deserializer = Self::new(kdl);
+        deserializer.errors = Some(Vec::new());
+        let document = deserializer.parse().map_err(|error| vec![error])?;
+
+        let mut typed_partial =
+            Partial::alloc::<T>().map_err(|error| vec![KdlError::from(error)])?;
+        let result = deserializer.deserialize_document(typed_partial.inner_mut(), document, None);
+
+        let mut errors = deserializer.errors.take().unwrap_or_default();
+        errors.extend(result.err());
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let boxed_value = typed_partial
+            .build()
+            .map_err(|error| vec![KdlError::from(error)])?;
+        Ok(*boxed_value)
+    }
+
+    fn parse(&self) -> Result<KdlDocument> {
+        // PERF: This definitely isn't zero-copy, so it might be worth seeing if that's something that can be added to
+        // `kdl-rs` at some point in the future?
+        // PERF: Would be be better / quicker if I did this parsing incrementally? Using information from the `Partial` to
+        // decide when to call `KdlNode::parse` and `KdlEntry::parse`? Probably would be if I'm only trying to parse
+        // some of the KDL text, but I'm not so sure otherwise? Will need benchmarking...
+        self.check_depth()?;
+        let document = self.kdl.parse().map_err(|error: kdl::KdlError| {
+            let span = error.diagnostics.first().map(|diagnostic| {
+                Span::new(self.kdl, diagnostic.span.offset(), diagnostic.span.len())
+            });
+            let error = KdlError::from(error);
+            match span {
+                Some(span) => error.located(span, &self.source_code),
+                None => error,
+            }
+        })?;
+        log::trace!("KDL parsed");
+
+        Ok(document)
+    }
+
+    /// Turns away documents with children blocks nested more than [`MAX_DEPTH`] deep, since `kdl-rs` parses them
+    /// recursively and could overflow the stack before deserialization ever gets to them.
+    ///
+    /// Braces inside strings and comments are counted too, so this can only ever overestimate how deep the nesting is.
+    fn check_depth(&self) -> Result<()> {
+        let mut depth = 0usize;
+        for (offset, byte) in self.kdl.bytes().enumerate() {
+            match byte {
+                b'{' => depth += 1,
+                b'}' => depth = depth.saturating_sub(1),
+                _ => continue,
+            }
+            if depth > MAX_DEPTH {
+                let error = KdlError::from(KdlErrorKind::TooDeep(MAX_DEPTH));
+                return Err(error.located(Span::new(self.kdl, offset, 1), &self.source_code));
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserializes a whole `document` into the struct or list `wip` is building.
+    ///
+    /// For lists, each top-level node is one element, and if `node_name` is given every node must be called that.
     fn deserialize_document(
         &mut self,
         wip: &mut Partial<'facet>,
         document: KdlDocument,
+        node_name: Option<&str>,
     ) -> Result<()> {
         log::trace!("Entering `deserialize_document` method");
 
@@ -421,86 +943,757 @@ impl<'input, 'facet> KdlDeserializer<'input> {
             // A struct can be at the top level
             // We'll handle it as a valid document structure
             log::trace!("Processing struct at document level");
-            return self.deserialize_node(wip, document);
+            let fields = fields_of(wip);
+            self.deserialize_children(wip, &document)?;
+            let result = self.fill_missing_fields(wip, fields, None, Some(&document));
+            return self.locate(result, &document);
         }
 
-        // Fall back to the def system for backward compatibility
-        let def = wip.shape().def;
-        match def {
-            // TODO: Valid if the list contains only enums with single fields that can be parsed as entries?
-            Def::List(_list_def) => todo!(),
-            _ => todo!(),
+        if let Def::List(_) = wip.shape().def {
+            log::trace!("Processing list at document level");
+            let nodes: Vec<&KdlNode> = document.nodes().iter().collect();
+            let result = self.deserialize_list_items(wip, &nodes, node_name);
+            return self.locate(result, &document);
         }
+
+        let error = KdlError::from(KdlErrorKind::UnsupportedRoot(wip.shape()));
+        self.locate(Err(error), &document)
     }
 
-    fn deserialize_node(&mut self, wip: &mut Partial<'facet>, document: KdlDocument) -> Result<()> {
+    fn deserialize_node(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        node: &KdlNode,
+        target: &Target,
+    ) -> Result<()> {
         log::trace!("Entering `deserialize_node` method");
 
-        // Process all nodes in the document
-        for node in document.nodes() {
-            log::trace!("Processing node: {:#?}", node.name());
-
-            // Check if this is a property (no children) or a child node
-            if node.children().is_none() && !node.entries().is_empty() {
-                // This looks like properties at the root level
-                for entry in node.entries() {
-                    if let Some(name) = entry.name() {
-                        // Named property
-                        self.deserialize_property(wip, name.value(), entry.value())?;
-                    } else {
-                        // Positional property (using node name as field name)
-                        self.deserialize_property(wip, node.name().value(), entry.value())?;
+        match wip.shape().def {
+            Def::Map(_) => return self.deserialize_map(wip, node, target),
+            // NOTE: A node with nothing but `#null` is `None`, like `quota #null`, and any other node is `Some`
+            Def::Option(_) => {
+                let is_null = match (node.entries(), node.children()) {
+                    ([entry], None) => {
+                        entry.name().is_none() && matches!(entry.value(), kdl::KdlValue::Null)
                     }
+                    _ => false,
+                };
+                if is_null {
+                    wip.set_default()?;
+                    return Ok(());
                 }
-            } else {
-                // Original logic for child nodes
-                // Try to match the node name with a field
-                wip.begin_field(node.name().value())?;
-                log::trace!(
-                    "Node matched expected child; New def: {:#?}",
-                    wip.shape().def
-                );
-
-                // Process entries (arguments and properties)
-                let mut arg_index = 0;
-                for entry in node.entries() {
-                    log::trace!("Processing entry: {entry:#?}");
-
-                    if entry.name().is_none() {
-                        // This is an argument - need to begin the field by index
-                        wip.begin_nth_field(arg_index)?;
-                        self.deserialize_value(wip, entry.value())?;
-                        wip.end()?;
-                        arg_index += 1;
-                    } else {
-                        // This is a property
-                        self.deserialize_property(
-                            wip,
-                            entry.name().unwrap().value(),
-                            entry.value(),
-                        )?;
+                wip.begin_some()?;
+                self.deserialize_node(wip, node, target)?;
+                return self.end(wip);
+            }
+            _ => {}
+        }
+
+        // NOTE: Arrays, tuples and tuple structs are just the node's arguments, like `point 1.0 2.0`
+        if let Some(len) = positional_len(wip.shape()) {
+            return self.deserialize_positional(wip, len, node, target);
+        }
+
+        match &wip.shape().ty {
+            Type::User(UserType::Struct(_)) => self.deserialize_fields(wip, node),
+            Type::User(UserType::Enum(_)) => {
+                // An enum is written as a node with a single child named after its variant, like
+                // `listener { tcp port=80; }`, or with the name of a unit variant as its argument, like `mode "fast"`.
+                // In `#[facet(annotated)]` fields, the node's type annotation can name the variant instead, like
+                // `(circle)shape r=1.0`, and in `#[facet(tag = "...")]` fields, a property of the node does, like
+                // `backend type="s3" bucket="logs"`. In `#[facet(untagged)]` fields, the first variant that fits wins.
+                match (tagging(self.field), node.ty()) {
+                    (Tagging::Annotation, Some(annotation)) => {
+                        return self.deserialize_variant(wip, annotation, node, target);
+                    }
+                    (Tagging::Property(tag), _) => {
+                        return self.deserialize_tagged_node(wip, tag, node, target);
+                    }
+                    (Tagging::Untagged, _) => {
+                        return self.deserialize_untagged(wip, node, |this, wip, variant| {
+                            this.fill_variant(wip, variant, node, target)
+                        });
                     }
+                    _ => {}
                 }
+                match (node.entries(), node.children().map(KdlDocument::nodes)) {
+                    ([], Some([variant_node])) => {
+                        self.deserialize_variant(wip, variant_node.name(), variant_node, target)
+                    }
+                    ([entry], None) if entry.name().is_none() => {
+                        let result = self.deserialize_entry(wip, entry, target);
+                        self.locate(result, entry)
+                    }
+                    _ => Err(mismatch(target, wip.shape(), Found::node(node))),
+                }
+            }
+            // Nodes that aren't filling in a struct are just a name and a value, like `version "1.0.0"`
+            _ => match node.entries() {
+                [entry] if entry.name().is_none() && node.children().is_none() => {
+                    let result = self.deserialize_value(wip, entry.value(), target);
+                    self.locate(result, entry)
+                }
+                _ => Err(mismatch(target, wip.shape(), Found::node(node))),
+            },
+        }
+    }
+
+    /// Deserializes the arguments of `node` as the `len` elements of the array, tuple or tuple struct `wip` is
+    /// building, like `rgb 255 128 0`.
+    fn deserialize_positional(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        len: usize,
+        node: &KdlNode,
+        target: &Target,
+    ) -> Result<()> {
+        let (arguments, properties): (Vec<&KdlEntry>, Vec<&KdlEntry>) = self
+            .entries(node)
+            .into_iter()
+            .partition(|entry| entry.name().is_none());
+        if !properties.is_empty() || node.children().is_some() {
+            return Err(mismatch(target, wip.shape(), Found::node(node)));
+        }
+        if arguments.len() != len {
+            return Err(KdlErrorKind::Arity {
+                parent: target.parent,
+                field: target.field.clone(),
+                expected: len,
+                found: arguments.len(),
+            }
+            .into());
+        }
 
-                // Process child nodes if any
-                if let Some(children) = node.children() {
-                    log::trace!("Node has children, processing them");
-                    self.deserialize_children(wip, children)?;
+        let is_array = matches!(wip.shape().def, Def::Array(_));
+        let fields = fields_of(wip);
+        for (index, entry) in arguments.into_iter().enumerate() {
+            let target = Target {
+                role: Role::Argument(index),
+                parent: target.parent,
+                field: target.field.clone(),
+            };
+            self.visit(PathSegment::Index(index), entry, |this| {
+                if is_array {
+                    wip.begin_nth_element(index)?;
+                } else {
+                    this.begin_field(wip, fields, index)?;
                 }
+                this.deserialize_entry(wip, entry, &target)?;
+                this.end(wip)
+            })?;
+        }
 
-                // End the field after processing all entries and children
-                log::trace!("Ending field for node: {}", node.name().value());
-                wip.end()?;
+        Ok(())
+    }
+
+    /// Deserializes a `node` that doesn't have a name of its own, like an element of a list.
+    ///
+    /// That's the same as [`Self::deserialize_node`], except that the node's name picks the variant of an enum, unless
+    /// the enum only has unit variants and the node doesn't name one of them, like `mode "fast"`, or the variant is
+    /// picked by type annotations instead.
+    fn deserialize_element(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        node: &KdlNode,
+        target: &Target,
+    ) -> Result<()> {
+        let shape = wip.shape();
+        let names_variant = enum_variants(shape)
+            .iter()
+            .any(|variant| variant.name == node.name().value());
+        let by_name = tagging(self.field) == Tagging::External;
+        if by_name && (has_data_variants(shape) || names_variant) {
+            self.deserialize_variant(wip, node.name(), node, target)
+        } else {
+            self.deserialize_node(wip, node, target)
+        }
+    }
+
+    /// Deserializes `node` as the variant of the enum `wip` is building called `name`, like `tcp port=80` or
+    /// `unix "/run/app.sock"`.
+    ///
+    /// `name` is usually the node's own name, but it can also be its type annotation, like the `circle` in
+    /// `(circle)shape r=1.0`.
+    fn deserialize_variant(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        name: &KdlIdentifier,
+        node: &KdlNode,
+        target: &Target,
+    ) -> Result<()> {
+        let variant = self.select_variant(wip, name.value(), name)?;
+        self.fill_variant(wip, variant, node, target)
+    }
+
+    /// Deserializes `node` as the variant of the enum `wip` is building named by its `tag` property, like
+    /// `backend type="s3" bucket="logs"`. The node's other entries and children fill in the variant.
+    fn deserialize_tagged_node(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        tag: &str,
+        node: &KdlNode,
+        target: &Target,
+    ) -> Result<()> {
+        let is_tag = |entry: &KdlEntry| entry.name().is_some_and(|name| name.value() == tag);
+        let Some(tag_entry) = node.entries().iter().find(|entry| is_tag(entry)) else {
+            let missing = format!("property `{tag}` of `{}`", node.name().value());
+            return Err(KdlErrorKind::MissingNodes(vec![missing]).into());
+        };
+        let kdl::KdlValue::String(name) = tag_entry.value() else {
+            let error = mismatch(target, wip.shape(), Found::Value(tag_entry.value().clone()));
+            return self.locate(Err(error), tag_entry);
+        };
+        let variant = self.select_variant(wip, name, tag_entry)?;
+
+        let outer_tag_entry = self.tag_entry.replace(tag_entry.byte_range());
+        let result = self.fill_variant(wip, variant, node, target);
+        self.tag_entry = outer_tag_entry;
+        result
+    }
+
+    /// Deserializes `node` into `variant`, which has been selected in the enum `wip` is building.
+    fn fill_variant(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        variant: &'static Variant,
+        node: &KdlNode,
+        target: &Target,
+    ) -> Result<()> {
+        // NOTE: A variant wrapping a single struct reads that struct from the node, like `tcp port=80` for `Tcp(Tcp)`
+        let fields = variant.data.fields;
+        if let [field] = fields {
+            if !matches!(variant.data.kind, StructKind::Struct)
+                && matches!(field.shape().ty, Type::User(UserType::Struct(_)))
+            {
+                wip.begin_nth_field(0)?;
+                self.deserialize_node(wip, node, target)?;
+                return self.end(wip);
             }
         }
 
+        self.deserialize_fields(wip, node)
+    }
+
+    /// Selects the variant of the enum `wip` is building called `name`, which was given by `item`.
+    fn select_variant(
+        &self,
+        wip: &mut Partial<'facet>,
+        name: &str,
+        item: &impl Spanned,
+    ) -> Result<&'static Variant> {
+        let shape = wip.shape();
+        let variants = enum_variants(shape);
+        log::trace!("Selecting variant `{name}` of `{shape}`");
+
+        let Some(variant) = variants.iter().find(|variant| variant.name == name) else {
+            let error = KdlError::from(KdlErrorKind::UnknownVariant {
+                shape,
+                name: name.to_owned(),
+                expected: variants.iter().map(|variant| variant.name).collect(),
+            });
+            return self.locate(Err(error), item);
+        };
+        wip.select_variant_named(variant.name)?;
+        Ok(variant)
+    }
+
+    /// Deserializes the entries and children of `node` into the fields of the struct or enum variant `wip` is
+    /// building.
+    fn deserialize_fields(&mut self, wip: &mut Partial<'facet>, node: &KdlNode) -> Result<()> {
+        // Process entries (arguments and properties)
+        let shape = wip.shape();
+        let fields = fields_of(wip);
+        let arguments_field = fields
+            .iter()
+            .position(|field| has_kdl_attr(field, "arguments"));
+        let properties_field = fields
+            .iter()
+            .position(|field| has_kdl_attr(field, "properties"));
+        let mut rest = Vec::new();
+        let mut extra = Vec::new();
+        let mut arg_index = 0;
+        for entry in self.entries(node) {
+            log::trace!("Processing entry: {entry:#?}");
+
+            if let Some(name) = entry.name() {
+                // NOTE: Properties without a `property` field of their own go to the `properties` field, if there is
+                // one, even when they're named like some other field
+                let is_extra = properties_field.is_some()
+                    && !fields
+                        .iter()
+                        .any(|field| field.name == name.value() && has_kdl_attr(field, "property"));
+                if is_extra {
+                    extra.push(entry);
+                } else {
+                    self.recover(wip, shape, |this, wip| {
+                        this.deserialize_property(wip, name.value(), entry)
+                    })?;
+                }
+            } else {
+                // NOTE: Once the `argument` fields are full, the rest go to the `arguments` field, if there is one
+                if arguments_field.is_some() && argument_field(fields, arg_index).is_none() {
+                    rest.push(entry);
+                } else {
+                    self.recover(wip, shape, |this, wip| {
+                        this.deserialize_argument(wip, arg_index, entry)
+                    })?;
+                }
+                arg_index += 1;
+            }
+        }
+
+        if let Some(field_index) = arguments_field {
+            let first_index = arg_index - rest.len();
+            self.recover(wip, shape, |this, wip| {
+                this.deserialize_arguments(wip, field_index, first_index, &rest, node)
+            })?;
+        }
+        if let Some(field_index) = properties_field {
+            self.recover(wip, shape, |this, wip| {
+                this.deserialize_extra_properties(wip, field_index, &extra, node)
+            })?;
+        }
+
+        // Process child nodes if any
+        if let Some(children) = node.children() {
+            log::trace!("Node has children, processing them");
+            self.deserialize_children(wip, children)?;
+        }
+
+        self.fill_missing_fields(wip, fields, Some(node), node.children())
+    }
+
+    /// Deserializes the children of `node` as the entries of the map `wip` is building, keyed by their names.
+    fn deserialize_map(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        node: &KdlNode,
+        target: &Target,
+    ) -> Result<()> {
+        // Maps are written as a block of children, like `env { NODE_ENV "production"; }`
+        let map = wip.shape();
+        if !node.entries().is_empty() {
+            return Err(mismatch(target, map, Found::node(node)));
+        }
+        wip.begin_map()?;
+
+        let Some(children) = node.children() else {
+            return Ok(());
+        };
+        for child_node in children.nodes() {
+            let key = child_node.name().value();
+            log::trace!("Processing map entry: {key}");
+
+            let target = Target {
+                role: Role::Child,
+                parent: map,
+                field: key.to_owned(),
+            };
+            self.recover(wip, map, |this, wip| {
+                this.visit(PathSegment::Key(key.to_owned()), child_node, |this| {
+                    this.deserialize_map_entry(wip, key, &target, |this, wip| {
+                        this.deserialize_node(wip, child_node, &target)
+                    })
+                })
+            })?;
+        }
+
         Ok(())
     }
+
+    /// Adds an entry called `key` to the map `wip` is building, using `value` to deserialize its value.
+    fn deserialize_map_entry(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        key: &str,
+        target: &Target,
+        value: impl FnOnce(&mut Self, &mut Partial<'facet>) -> Result<()>,
+    ) -> Result<()> {
+        // NOTE: After an earlier failure, `wip` is a fresh scratch map that hasn't been started yet
+        if self.has_failed() {
+            wip.begin_map()?;
+        }
+        wip.begin_key()?;
+        self.deserialize_value(wip, &kdl::KdlValue::String(key.to_owned()), target)?;
+        self.end(wip)?;
+        wip.begin_value()?;
+        value(self, wip)?;
+        self.end(wip)
+    }
+
+    /// Makes sure that every one of the `fields` of the struct `wip` is building has been filled in, defaulting the
+    /// fields that can be and reporting all of the others in one error.
+    ///
+    /// The `fields` are taken before any entries or children are deserialized, since after a failure `wip` is a scratch
+    /// value of some other shape. `node` is the node the struct was read from, or `None` if it's the whole document.
+    fn fill_missing_fields(
+        &mut self,
+        wip: &mut Partial<'facet>,
+        fields: &'static [Field],
+        node: Option<&KdlNode>,
+        children: Option<&KdlDocument>,
+    ) -> Result<()> {
+        // NOTE: After an earlier failure, `wip` can't be trusted to know which of its fields have been set, so the KDL
+        // is checked for them instead. Nothing will be built from `wip` then, so there's no need for defaults either.
+        let has_failed = self.has_failed();
+        let mut missing = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            let is_set = if has_failed {
+                is_supplied(fields, index, node, children)
+            } else {
+                wip.is_field_set(index)?
+            };
+            if is_set {
+                continue;
+            }
+
+            // NOTE: A list field with no nodes or a map field with no entries is just empty, and an optional field
+            // that's left out is `None`
+            if field.flags.contains(FieldFlags::DEFAULT)
+                || is_list(field.shape())
+                || matches!(field.shape().def, Def::Map(_) | Def::Option(_))
+            {
+                if has_failed {
+                    continue;
+                }
+                log::trace!("Defaulting missing field `{}`", field.name);
+                wip.begin_nth_field(index)?;
+                wip.set_default()?;
+                wip.end()?;
+            } else {
+                missing.push(describe_field(fields, index, node));
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(KdlErrorKind::MissingNodes(missing).into())
+        }
+    }
+}
+
+/// An error for when `found` can't be used as the `expected` value of `target`.
+fn mismatch(target: &Target, expected: &'static Shape, found: Found) -> KdlError {
+    KdlError::from(KdlErrorKind::Mismatch {
+        target: target.clone(),
+        expected,
+        found,
+    })
+}
+
+/// The fields of `shape`, if it's a struct.
+fn struct_fields(shape: &'static Shape) -> &'static [Field] {
+    match &shape.ty {
+        Type::User(UserType::Struct(struct_type)) => struct_type.fields,
+        _ => &[],
+    }
+}
+
+/// The number of arguments of the node holding a value of `shape`, if that's all there is to it, like `point 1.0 2.0`
+/// for a `(f64, f64)` or `rgb 255 128 0` for a `[u8; 3]`.
+///
+/// That's the case for arrays, and for tuples and tuple structs whose fields aren't marked with where they go.
+fn positional_len(shape: &'static Shape) -> Option<usize> {
+    match (&shape.ty, &shape.def) {
+        (_, Def::Array(array_def)) => Some(array_def.n),
+        (Type::User(UserType::Struct(struct_type)), _)
+            if matches!(
+                struct_type.kind,
+                StructKind::Tuple | StructKind::TupleStruct
+            ) =>
+        {
+            let is_marked = struct_type.fields.iter().any(|field| {
+                [
+                    "argument",
+                    "arguments",
+                    "property",
+                    "properties",
+                    "child",
+                    "children",
+                ]
+                .iter()
+                .any(|attr| has_kdl_attr(field, attr))
+            });
+            (!is_marked).then_some(struct_type.fields.len())
+        }
+        _ => None,
+    }
+}
+
+/// Whether `shape` is a list that repeated nodes can be collected into, like a `Vec`, `VecDeque` or `Box<[T]>`.
+fn is_list(shape: &'static Shape) -> bool {
+    list_element(shape).is_some()
+}
+
+/// The type of the elements of `shape`, if it's a list that repeated nodes can be collected into.
+fn list_element(shape: &'static Shape) -> Option<&'static Shape> {
+    match &shape.def {
+        Def::List(list_def) => Some(list_def.t()),
+        Def::Pointer(pointer_def) => match pointer_def.pointee()?.def {
+            Def::Slice(slice_def) => Some(slice_def.t()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The variants of `shape`, if it's an enum.
+fn enum_variants(shape: &'static Shape) -> &'static [Variant] {
+    match &shape.ty {
+        Type::User(UserType::Enum(enum_type)) => enum_type.variants,
+        _ => &[],
+    }
+}
+
+/// Whether `shape` is an enum with at least one variant holding data, which is written as a node named after the
+/// variant.
+fn has_data_variants(shape: &'static Shape) -> bool {
+    enum_variants(shape)
+        .iter()
+        .any(|variant| !variant.data.fields.is_empty())
+}
+
+/// Whether the enum `shape` in `field` can be given as the discriminant of one of its variants, like `priority 3`.
+///
+/// That's the case for enums with explicit discriminants, and for any enum in a `#[facet(discriminant)]` field. Since
+/// the discriminants of an enum like `enum Mode { Fast, Slow }` are just `0` and `1`, they only count as explicit when
+/// at least one of them differs from the variant's position.
+fn accepts_codes(shape: &'static Shape, field: Option<&Field>) -> bool {
+    let has_explicit_codes = enum_variants(shape)
+        .iter()
+        .enumerate()
+        .any(|(index, variant)| {
+            variant
+                .discriminant
+                .is_some_and(|code| code != index as i64)
+        });
+    has_explicit_codes || field.is_some_and(|field| has_kdl_attr(field, "discriminant"))
+}
+
+/// The fields of the struct, or of the selected variant of the enum, that `wip` is building.
+fn fields_of(wip: &Partial<'_>) -> &'static [Field] {
+    match wip.shape().ty {
+        Type::User(UserType::Enum(_)) => wip
+            .selected_variant()
+            .map_or(&[], |variant| variant.data.fields),
+        _ => struct_fields(wip.shape()),
+    }
+}
+
+/// The index of the field in `fields` called `name`, or an error suggesting the fields it might be a typo of.
+///
+/// The `role` is the kind of KDL construct that `name` came from.
+fn field_named(
+    parent: &'static Shape,
+    fields: &'static [Field],
+    name: &str,
+    role: Role,
+) -> Result<usize> {
+    fields
+        .iter()
+        .position(|field| field.name == name)
+        .ok_or_else(|| {
+            KdlError::from(KdlErrorKind::UnknownField {
+                parent,
+                role,
+                name: name.to_owned(),
+                suggestions: suggest::suggestions(name, fields.iter().map(|field| field.name)),
+            })
+        })
+}
+
+/// Describes where the KDL for `fields[index]` should have been, like "argument #0 of `process`".
+fn describe_field(fields: &[Field], index: usize, node: Option<&KdlNode>) -> String {
+    let field = &fields[index];
+
+    // NOTE: At the top level of the document, every field is read from a node of the same name
+    let Some(node) = node else {
+        return format!("child node `{}`", field.name);
+    };
+    let node_name = node.name().value();
+
+    if has_kdl_attr(field, "argument") {
+        let position = fields[..index]
+            .iter()
+            .filter(|field| has_kdl_attr(field, "argument"))
+            .count();
+        format!("argument #{position} of `{node_name}`")
+    } else if has_kdl_attr(field, "arguments") {
+        format!("arguments of `{node_name}`")
+    } else if has_kdl_attr(field, "property") {
+        format!("property `{}` of `{node_name}`", field.name)
+    } else {
+        format!("child node `{}` of `{node_name}`", field.name)
+    }
+}
+
+/// Whether `node` or `children` have some KDL for `fields[index]`, whether or not it could be deserialized.
+///
+/// At the top level of the document, there's no `node` and every field is read from one of the `children`.
+fn is_supplied(
+    fields: &[Field],
+    index: usize,
+    node: Option<&KdlNode>,
+    children: Option<&KdlDocument>,
+) -> bool {
+    let field = &fields[index];
+    let in_children = children.is_some_and(|children| {
+        children
+            .nodes()
+            .iter()
+            .any(|child| child_field(fields, child.name().value()) == Some(index))
+    });
+    let Some(node) = node else {
+        return in_children;
+    };
+
+    if has_kdl_attr(field, "arguments") || has_kdl_attr(field, "properties") {
+        // NOTE: These are always deserialized, even from nothing, so any problem with them is reported already
+        true
+    } else if has_kdl_attr(field, "property") {
+        node.entries()
+            .iter()
+            .any(|entry| entry.name().is_some_and(|name| name.value() == field.name))
+    } else {
+        let arguments = node
+            .entries()
+            .iter()
+            .filter(|entry| entry.name().is_none())
+            .count();
+        in_children
+            || (0..arguments).any(|arg_index| argument_field(fields, arg_index) == Some(index))
+    }
+}
+
+/// How the variant of an enum is picked out in KDL, set with attributes on the field holding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tagging {
+    /// By the name of a node, like `tcp port=80`.
+    External,
+    /// By the type annotation of a node or value, like `(circle)shape r=1.0` or `(seconds)30`, for
+    /// `#[facet(annotated)]` fields.
+    Annotation,
+    /// By a property of the node, like `backend type="s3"`, for `#[facet(tag = "type")]` fields.
+    Property(&'static str),
+    /// By trying each variant in turn until one fits, for `#[facet(untagged)]` fields.
+    Untagged,
+}
+
+/// How enums in `field` pick their variant.
+fn tagging(field: Option<&Field>) -> Tagging {
+    let Some(field) = field else {
+        return Tagging::External;
+    };
+    if let Some(Some(tag)) = kdl_attr(field, "tag") {
+        Tagging::Property(tag)
+    } else if has_kdl_attr(field, "annotated") {
+        Tagging::Annotation
+    } else if has_kdl_attr(field, "untagged") {
+        Tagging::Untagged
+    } else {
+        Tagging::External
+    }
+}
+
+/// Whether `field` has been marked with `#[facet(<attr>)]` or `#[facet(<attr> = "...")]`.
+fn has_kdl_attr(field: &Field, attr: &str) -> bool {
+    kdl_attr(field, attr).is_some()
+}
+
+/// Looks for `#[facet(<attr>)]` or `#[facet(<attr> = "value")]` on `field`, returning `Some(None)` for the former and
+/// `Some(Some("value"))` for the latter.
+fn kdl_attr(field: &Field, attr: &str) -> Option<Option<&'static str>> {
+    field.attributes.iter().find_map(|field_attr| {
+        let FieldAttribute::Arbitrary(tokens) = field_attr else {
+            return None;
+        };
+        // NOTE: The tokens may or may not have spaces around the `=`, depending on how they were stringified
+        match tokens.split_once('=') {
+            None => (tokens.trim() == attr).then_some(None),
+            Some((name, value)) => {
+                (name.trim() == attr).then(|| Some(value.trim().trim_matches('"')))
+            }
+        }
+    })
+}
+
+/// The index of the field that a child node called `name` belongs to.
+///
+/// A field with the same name comes first, then a `#[facet(children = "name")]` field, then a list of enums with a
+/// variant called `name`, then a catch-all `#[facet(children)]` field, and finally the only unfiltered list of enums,
+/// if there's just one, so that a node that isn't any of its variants is reported as an unknown variant.
+fn child_field(fields: &[Field], name: &str) -> Option<usize> {
+    fields
+        .iter()
+        .position(|field| field.name == name && !has_kdl_attr(field, "children"))
+        .or_else(|| {
+            fields
+                .iter()
+                .position(|field| kdl_attr(field, "children") == Some(Some(name)))
+        })
+        .or_else(|| {
+            fields.iter().position(|field| {
+                list_element(field.shape()).is_some_and(|element| {
+                    enum_variants(element)
+                        .iter()
+                        .any(|variant| variant.name == name)
+                })
+            })
+        })
+        .or_else(|| {
+            fields
+                .iter()
+                .position(|field| kdl_attr(field, "children") == Some(None))
+        })
+        .or_else(|| {
+            let mut enum_lists = fields.iter().enumerate().filter(|(_, field)| {
+                !matches!(kdl_attr(field, "children"), Some(Some(_)))
+                    && list_element(field.shape())
+                        .is_some_and(|element| !enum_variants(element).is_empty())
+            });
+            match (enum_lists.next(), enum_lists.next()) {
+                (Some((index, _)), None) => Some(index),
+                _ => None,
+            }
+        })
+}
+
+/// The index of the field that should hold the `arg_index`th argument of a node.
+///
+/// Arguments fill the fields marked with `#[facet(argument)]` in order, or every field in order if none are marked with
+/// `argument` or `arguments`.
+fn argument_field(fields: &[Field], arg_index: usize) -> Option<usize> {
+    let mut argument_fields = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| has_kdl_attr(field, "argument"))
+        .map(|(index, _)| index)
+        .peekable();
+
+    if argument_fields.peek().is_none() {
+        if fields.iter().any(|field| has_kdl_attr(field, "arguments")) {
+            return None;
+        }
+        return (arg_index < fields.len()).then_some(arg_index);
+    }
+    argument_fields.nth(arg_index)
 }
 
 /// Deserialize a value of type `T` from a KDL string.
 ///
-/// Returns a [`KdlError`] if the input KDL is invalid or doesn't match `T`.
+/// Returns a [`KdlError`] if the input KDL is invalid or doesn't match `T`, or is nested more than 64 levels deep. This
+/// never panics, whatever the input, so it's safe to use on untrusted KDL.
+///
+/// An enum can also be given as the integer discriminant of one of its variants, like `priority 3`, but only if it's
+/// in a field marked with `#[facet(discriminant)]`, or if at least one of its discriminants is different from the
+/// variant's position. The discriminants are all that can be seen of an enum, so one written out in full as
+/// `enum Mode { Fast = 0, Slow = 1 }` can't be told apart from `enum Mode { Fast, Slow }`, and only accepts its codes
+/// in `#[facet(discriminant)]` fields.
+///
+/// A `#[facet(properties)]` field collects every property that isn't given to a `#[facet(property)]` field, keyed by
+/// its name, like `HashMap<String, String>`. Its values can be of any type that implements `Facet` and can be read
+/// from a single KDL value, which doesn't include `kdl::KdlValue` itself.
 ///
 /// # Example
 /// ```ignore
@@ -519,5 +1712,52 @@ where
 {
     log::trace!("Entering `from_str` function");
 
-    KdlDeserializer::from_str(kdl)
+    KdlDeserializer::from_str(kdl, None)
+}
+
+/// Deserialize a list of `T`s from a KDL string, where each top-level node is one element and must be called
+/// `node_name`.
+///
+/// [`from_str`] can also deserialize a `Vec<T>`, but accepts top-level nodes with any name.
+///
+/// # Example
+/// ```ignore
+/// let kdl = r#"
+/// process "web" command="node server.js"
+/// process "worker" command="node worker.js"
+/// "#;
+/// let processes: Vec<Process> = from_str_nodes(kdl, "process")?;
+/// ```
+pub fn from_str_nodes<'input, 'facet, T>(kdl: &'input str, node_name: &str) -> Result<Vec<T>>
+where
+    T: Facet<'facet>,
+    'input: 'facet,
+{
+    log::trace!("Entering `from_str_nodes` function");
+
+    KdlDeserializer::from_str(kdl, Some(node_name))
+}
+
+/// Deserialize a value of type `T` from a KDL string, reporting every problem with it rather than just the first.
+///
+/// When part of the document can't be deserialized, that node or entry is skipped and the rest of the document is
+/// still checked, so that all of the errors can be fixed in one go.
+///
+/// # Example
+/// ```ignore
+/// match from_str_all_errors::<MyStruct>(kdl) {
+///     Ok(val) => println!("{val:?}"),
+///     Err(errors) => errors.iter().for_each(|error| eprintln!("{error}")),
+/// }
+/// ```
+pub fn from_str_all_errors<'input, 'facet, T>(
+    kdl: &'input str,
+) -> std::result::Result<T, Vec<KdlError>>
+where
+    T: Facet<'facet>,
+    'input: 'facet,
+{
+    log::trace!("Entering `from_str_all_errors` function");
+
+    KdlDeserializer::from_str_all_errors(kdl)
 }
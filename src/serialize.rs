@@ -3,29 +3,171 @@ use std::{
     fmt::{self, Display},
 };
 
+use facet_core::{Def, Facet, Field, Shape, StructKind, Type, UserType, Variant};
 use facet_serialize::{Serialize, Serializer};
 use kdl::{KdlDocument, KdlEntry, KdlNode, KdlValue};
 
+use crate::{
+    Path, PathSegment, Tagging, enum_variants, has_data_variants, has_kdl_attr, kdl_attr,
+    positional_len, struct_fields, tagging,
+};
+
 /// Error type for KDL serialization.
 #[derive(Debug)]
 pub struct KdlSerializeError {
-    message: String,
+    kind: KdlSerializeErrorKind,
+    path: Path,
+}
+
+impl KdlSerializeError {
+    /// What went wrong.
+    pub fn kind(&self) -> &KdlSerializeErrorKind {
+        &self.kind
+    }
+
+    /// The field, element or map entry of the value that couldn't be serialized.
+    ///
+    /// This is empty when the value being serialized is the top-level value itself.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
 }
 
 impl Display for KdlSerializeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "KDL serialization error: {}", self.message)
+        write!(f, "KDL serialization error: {}", self.kind)?;
+        if !self.path.segments().is_empty() {
+            write!(f, " (at `{}`)", self.path)?;
+        }
+        Ok(())
     }
 }
 
 impl Error for KdlSerializeError {}
 
-impl KdlSerializeError {
-    fn new(message: impl Into<String>) -> Self {
-        Self {
-            message: message.into(),
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for KdlSerializeError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(self.kind.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.kind
+            .help()
+            .map(|help| Box::new(help) as Box<dyn Display>)
+    }
+}
+
+/// The different ways that serializing to KDL can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KdlSerializeErrorKind {
+    /// A value with no KDL equivalent, like a byte array.
+    UnsupportedType(&'static str),
+    /// A number too large to be written as a KDL integer.
+    OutOfRange(String),
+    /// A name that can't be used as the name of a KDL node or property.
+    InvalidIdentifier(String),
+    /// A value that can't be written as the KDL construct its field is marked as, like a struct in a
+    /// `#[facet(property)]` field.
+    RoleConflict {
+        /// The name of the field.
+        field: &'static str,
+        /// The construct the field is marked as, like "property".
+        role: &'static str,
+        /// What the field actually holds, like "a struct".
+        found: &'static str,
+    },
+}
+
+impl Display for KdlSerializeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KdlSerializeErrorKind::UnsupportedType(what) => {
+                write!(f, "{what} are not supported in KDL")
+            }
+            KdlSerializeErrorKind::OutOfRange(value) => {
+                write!(f, "{value} is too large for KDL")
+            }
+            KdlSerializeErrorKind::InvalidIdentifier(name) => {
+                write!(f, "`{name}` can't be used as a KDL node or property name")
+            }
+            KdlSerializeErrorKind::RoleConflict { field, role, found } => {
+                write!(
+                    f,
+                    "field `{field}` is marked as a KDL {role}, but holds {found}"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "miette")]
+impl KdlSerializeErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            KdlSerializeErrorKind::UnsupportedType(_) => "facet_kdl::serialize::unsupported_type",
+            KdlSerializeErrorKind::OutOfRange(_) => "facet_kdl::serialize::out_of_range",
+            KdlSerializeErrorKind::InvalidIdentifier(_) => {
+                "facet_kdl::serialize::invalid_identifier"
+            }
+            KdlSerializeErrorKind::RoleConflict { .. } => "facet_kdl::serialize::role_conflict",
         }
     }
+
+    fn help(&self) -> Option<&'static str> {
+        match self {
+            KdlSerializeErrorKind::UnsupportedType(_) => {
+                Some("KDL can only represent strings, numbers, booleans and nulls as values")
+            }
+            KdlSerializeErrorKind::OutOfRange(_) => {
+                Some("KDL integers must fit in an `i128`; consider storing this value as a string")
+            }
+            KdlSerializeErrorKind::InvalidIdentifier(_) => None,
+            KdlSerializeErrorKind::RoleConflict { .. } => Some(
+                "properties and arguments can only hold single values; mark the field with `#[facet(child)]` instead",
+            ),
+        }
+    }
+}
+
+/// One level of nesting inside the value being serialized.
+struct Frame {
+    /// The type of the struct, list, map or enum at this level, if it's known.
+    shape: Option<&'static Shape>,
+    kind: FrameKind,
+    /// How many nodes were started for this level, which are finished along with it.
+    nodes: usize,
+}
+
+enum FrameKind {
+    /// A struct or struct variant with the given `fields`, along with the field currently being serialized.
+    Object {
+        fields: &'static [Field],
+        field_name: Option<&'static str>,
+        field: Option<&'static Field>,
+    },
+    /// A list, along with the index of the element currently being serialized.
+    ///
+    /// When `item_node` is set, each element is written as a node with that name, rather than as values of the current
+    /// node.
+    Array {
+        index: usize,
+        item_node: Option<String>,
+    },
+    /// A map, along with the key of the entry whose value is currently being serialized.
+    ///
+    /// When `as_properties` is set, each entry is written as a property of the current node.
+    Map {
+        key: Option<String>,
+        as_properties: bool,
+    },
+    /// An enum with variants holding data, written as a node named after the variant, or as a node or value marked
+    /// with the variant in the way its field asks for.
+    Enum {
+        variant: Option<&'static Variant>,
+        tagging: Tagging,
+    },
 }
 
 /// Serializer for KDL documents.
@@ -34,6 +176,11 @@ pub struct KdlSerializer {
     pub current_node: Option<KdlNode>,
     pub node_stack: Vec<KdlNode>,
     pub current_key: Option<String>,
+    frames: Vec<Frame>,
+    /// The type of the next value to be serialized, if it's known.
+    next_shape: Option<&'static Shape>,
+    /// The type annotation for the next value, like the `seconds` in `(seconds)30`.
+    next_annotation: Option<&'static str>,
 }
 
 impl KdlSerializer {
@@ -44,6 +191,9 @@ impl KdlSerializer {
             current_node: None,
             node_stack: Vec::new(),
             current_key: None,
+            frames: Vec::new(),
+            next_shape: None,
+            next_annotation: None,
         }
     }
 
@@ -56,6 +206,425 @@ impl KdlSerializer {
     pub fn into_string(self) -> String {
         self.document.to_string()
     }
+
+    /// An error of the given `kind` for the value currently being serialized.
+    fn error(&self, kind: KdlSerializeErrorKind) -> KdlSerializeError {
+        let path = self
+            .frames
+            .iter()
+            .filter_map(|frame| match &frame.kind {
+                FrameKind::Object { field_name, .. } => {
+                    field_name.map(|name| PathSegment::Field(name.to_owned()))
+                }
+                FrameKind::Array { index, .. } => Some(PathSegment::Index(*index)),
+                FrameKind::Map { key, .. } => key.clone().map(PathSegment::Key),
+                FrameKind::Enum { .. } => None,
+            })
+            .collect();
+        KdlSerializeError { kind, path }
+    }
+
+    /// The innermost struct, list, map or enum that values are written to.
+    ///
+    /// That skips enums that don't have a node named after their variant and haven't started a node of their own,
+    /// since their variant's value or node stands in for them, like `(seconds)30`.
+    fn top(&self) -> Option<&Frame> {
+        self.frames.iter().rev().find(|frame| match frame.kind {
+            FrameKind::Enum { tagging, .. } => tagging == Tagging::External || frame.nodes > 0,
+            _ => true,
+        })
+    }
+
+    /// The field currently being serialized, if its definition is known.
+    fn current_field(&self) -> Option<&'static Field> {
+        match self.top() {
+            Some(Frame {
+                kind: FrameKind::Object { field, .. },
+                ..
+            }) => *field,
+            _ => None,
+        }
+    }
+
+    /// The field the current value belongs to, looking through the lists it holds to the field itself.
+    fn owning_field(&self) -> Option<&'static Field> {
+        self.frames
+            .iter()
+            .rev()
+            .find_map(|frame| match frame.kind {
+                FrameKind::Object { field, .. } => Some(field),
+                FrameKind::Array { .. } => None,
+                FrameKind::Map { .. } | FrameKind::Enum { .. } => Some(None),
+            })?
+    }
+
+    /// The name of the node the next value should be written as, if it isn't just a value of the current node.
+    ///
+    /// That's the case for the elements of lists that are written one node per element, for the values of maps, and for
+    /// fields that are written as child nodes, which includes every field of a struct written as a whole document.
+    /// Taking the name of a field also clears `current_key`, so it isn't used for anything else.
+    fn take_node_name(&mut self, is_value: bool) -> Option<String> {
+        match self.top() {
+            Some(Frame {
+                kind:
+                    FrameKind::Array {
+                        item_node: Some(name),
+                        ..
+                    },
+                ..
+            }) => Some(name.clone()),
+            Some(Frame {
+                kind:
+                    FrameKind::Object {
+                        field_name: Some(_),
+                        field,
+                        ..
+                    },
+                ..
+            }) if !is_value
+                || field.is_some_and(|field| has_kdl_attr(field, "child"))
+                || self.current_node.is_none() =>
+            {
+                self.current_key.take()
+            }
+            Some(Frame {
+                kind: FrameKind::Map { key: Some(key), .. },
+                ..
+            }) => Some(key.clone()),
+            _ => None,
+        }
+    }
+
+    /// The name for nodes holding elements of the list about to be serialized, when nothing else names them.
+    fn default_item_node(&self) -> String {
+        self.next_shape
+            .and_then(element_shape)
+            .map_or_else(|| "-".to_owned(), default_node_name)
+    }
+
+    /// Writes a single value, as an argument or property of the current node or as a node of its own.
+    fn push_value(&mut self, value: KdlValue) -> Result<(), KdlSerializeError> {
+        // NOTE: The first value of each map entry is its key, which names the node or property holding its value
+        if let Some(Frame {
+            kind: FrameKind::Map { key: None, .. },
+            ..
+        }) = self.top()
+        {
+            let name = match value {
+                KdlValue::String(s) => s,
+                KdlValue::Integer(n) => n.to_string(),
+                KdlValue::Bool(b) => b.to_string(),
+                other => {
+                    let name = other.to_string();
+                    return Err(self.error(KdlSerializeErrorKind::InvalidIdentifier(name)));
+                }
+            };
+            if let Some(Frame {
+                kind: FrameKind::Map { key, .. },
+                ..
+            }) = self.frames.last_mut()
+            {
+                *key = Some(name);
+            }
+            return Ok(());
+        }
+
+        let property = match self.top() {
+            Some(Frame {
+                kind:
+                    FrameKind::Map {
+                        key: Some(key),
+                        as_properties: true,
+                    },
+                ..
+            }) => Some(key.clone()),
+            _ => None,
+        };
+        if let Some(key) = property {
+            let entry = self.new_entry(Some(key), value);
+            if let Some(ref mut node) = self.current_node {
+                node.push(entry);
+            }
+            self.value_done();
+            return Ok(());
+        }
+
+        if let Some(name) = self.take_node_name(true) {
+            let mut node = KdlNode::new(name);
+            node.push(self.new_entry(None, value));
+            self.attach_node(node);
+        } else {
+            // NOTE: The fields of tuples and tuple structs are the arguments of their node, like `point 1.0 2.0`
+            let is_argument = self
+                .current_field()
+                .is_some_and(|field| has_kdl_attr(field, "argument"))
+                || matches!(
+                    self.top(),
+                    Some(Frame {
+                        shape: Some(shape),
+                        kind: FrameKind::Object { .. },
+                        ..
+                    }) if positional_len(shape).is_some()
+                );
+            let key = self.current_key.take().filter(|_| !is_argument);
+            let entry = self.new_entry(key, value);
+            if let Some(ref mut node) = self.current_node {
+                node.push(entry);
+            }
+        }
+
+        self.value_done();
+        Ok(())
+    }
+
+    /// An entry holding `value`, as a property called `key` if one is given, with the pending type annotation if there
+    /// is one.
+    fn new_entry(&mut self, key: Option<String>, value: KdlValue) -> KdlEntry {
+        let mut entry = match key {
+            Some(key) => KdlEntry::new_prop(key, value),
+            None => KdlEntry::new(value),
+        };
+        if let Some(annotation) = self.next_annotation.take() {
+            entry.set_ty(annotation);
+        }
+        entry
+    }
+
+    /// Checks that the current field can hold `found`, which needs a node of its own.
+    fn check_role(&self, found: &'static str) -> Result<(), KdlSerializeError> {
+        if let Some(Frame {
+            kind:
+                FrameKind::Object {
+                    field_name: Some(name),
+                    field: Some(field),
+                    ..
+                },
+            ..
+        }) = self.top()
+        {
+            for role in ["property", "argument"] {
+                if has_kdl_attr(field, role) {
+                    return Err(self.error(KdlSerializeErrorKind::RoleConflict {
+                        field: *name,
+                        role,
+                        found,
+                    }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts a nested struct, list, map or enum, written as a node called `node_name` if one is given.
+    fn push_frame(
+        &mut self,
+        kind: FrameKind,
+        found: &'static str,
+        node_name: Option<String>,
+    ) -> Result<(), KdlSerializeError> {
+        // NOTE: Lists are still allowed in properties and arguments, and are written as several values. Enums tagged
+        // some other way than by node names are checked once their variant is known, in `start_variant`.
+        let is_checked_later = match kind {
+            FrameKind::Array { .. } => true,
+            FrameKind::Enum { tagging, .. } => tagging != Tagging::External,
+            _ => false,
+        };
+        if !is_checked_later {
+            self.check_role(found)?;
+        }
+
+        let nodes = usize::from(node_name.is_some());
+        if let Some(name) = node_name {
+            self.open_node(name);
+        }
+
+        let shape = self.next_shape.take();
+        self.frames.push(Frame { shape, kind, nodes });
+        self.element_started();
+        Ok(())
+    }
+
+    /// Finishes the innermost struct, list, map or enum.
+    fn pop_frame(&mut self) {
+        self.next_annotation = None;
+        if let Some(frame) = self.frames.pop() {
+            for _ in 0..frame.nodes {
+                self.close_node();
+            }
+        }
+        self.value_done();
+    }
+
+    /// Starts a new node called `name`, which everything is written to until it's closed.
+    fn open_node(&mut self, name: impl Into<String>) {
+        if let Some(parent) = self.current_node.take() {
+            self.node_stack.push(parent);
+        }
+        self.current_node = Some(KdlNode::new(name.into()));
+    }
+
+    /// Finishes the current node, going back to the one it's in.
+    fn close_node(&mut self) {
+        if let Some(node) = self.current_node.take() {
+            self.current_node = self.node_stack.pop();
+            self.attach_node(node);
+        }
+    }
+
+    /// How the enum about to be serialized marks its variant, as set by the field it belongs to.
+    fn tagging(&self) -> Tagging {
+        tagging(self.owning_field())
+    }
+
+    /// Starts writing the variant called `name` of the innermost enum.
+    fn start_variant(&mut self, name: &'static str) -> Result<(), KdlSerializeError> {
+        let Some(Frame {
+            shape,
+            kind: FrameKind::Enum { tagging, .. },
+            ..
+        }) = self.frames.last()
+        else {
+            return Ok(());
+        };
+        let tagging = *tagging;
+        let variant = shape.and_then(|shape| {
+            enum_variants(shape)
+                .iter()
+                .find(|variant| variant.name == name)
+        });
+        let payload = variant.and_then(|variant| match variant.data.fields {
+            [field] if !matches!(variant.data.kind, StructKind::Struct) => Some(field.shape()),
+            _ => None,
+        });
+        if let Some(Frame {
+            kind: FrameKind::Enum {
+                variant: selected, ..
+            },
+            ..
+        }) = self.frames.last_mut()
+        {
+            *selected = variant;
+        }
+        self.next_shape = payload;
+
+        match tagging {
+            Tagging::External => {
+                // An enum's "field" is its variant, which names the node holding the variant's data. A variant holding
+                // a single value has it as the node's argument, like `unix "/run/app.sock"`.
+                self.current_key = None;
+                self.open_node(name);
+            }
+            Tagging::Annotation | Tagging::Untagged if payload.is_some_and(is_value) => {
+                // The variant's value stands in for the enum, annotated with the variant if that's what picks it, like
+                // `timeout (seconds)30`
+                if tagging == Tagging::Annotation {
+                    self.next_annotation = Some(name);
+                }
+                return Ok(());
+            }
+            _ => self.open_tagged_node(tagging, name)?,
+        }
+        if let Some(frame) = self.frames.last_mut() {
+            frame.nodes += 1;
+        }
+        Ok(())
+    }
+
+    /// Starts the node holding an enum that's marked as the variant called `variant` according to `tagging`, like
+    /// `(circle)shape` or `backend type="s3"`.
+    fn open_tagged_node(
+        &mut self,
+        tagging: Tagging,
+        variant: &'static str,
+    ) -> Result<(), KdlSerializeError> {
+        self.check_role("an enum")?;
+        let node_name = self.take_node_name(false).unwrap_or_default();
+        self.current_key = None;
+        self.open_node(node_name);
+        if let Some(ref mut node) = self.current_node {
+            match tagging {
+                Tagging::Annotation => {
+                    node.set_ty(variant);
+                }
+                // NOTE: The node was just started, so the tag comes before everything else
+                Tagging::Property(tag) => {
+                    node.push(KdlEntry::new_prop(tag, variant));
+                }
+                Tagging::External | Tagging::Untagged => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the innermost level is a list that's written one node per element.
+    fn in_node_list(&self) -> bool {
+        matches!(
+            self.top(),
+            Some(Frame {
+                kind: FrameKind::Array {
+                    item_node: Some(_),
+                    ..
+                },
+                ..
+            })
+        )
+    }
+
+    /// The name of the node wrapping an enum's variant node, if it has one.
+    ///
+    /// In lists written one node per element, the variant's node is the element's node, so it isn't wrapped.
+    fn take_enum_wrapper(&mut self) -> Option<String> {
+        if self.in_node_list() {
+            None
+        } else {
+            self.take_node_name(false)
+        }
+    }
+
+    /// Adds a finished `node` as the last child of the current node, or to the document if there isn't one.
+    fn attach_node(&mut self, node: KdlNode) {
+        match &mut self.current_node {
+            Some(parent) => parent.ensure_children().nodes_mut().push(node),
+            None => self.document.nodes_mut().push(node),
+        }
+    }
+
+    /// Records that the next value of the innermost list is about to be serialized.
+    fn element_started(&mut self) {
+        if let Some(Frame {
+            shape: Some(shape),
+            kind: FrameKind::Array { .. },
+            ..
+        }) = self.frames.last()
+        {
+            self.next_shape = element_shape(shape);
+        }
+    }
+
+    /// Records that a complete value has been serialized.
+    fn value_done(&mut self) {
+        self.next_shape = None;
+        match self.frames.last_mut().map(|frame| &mut frame.kind) {
+            Some(FrameKind::Object {
+                field_name, field, ..
+            }) => {
+                *field_name = None;
+                *field = None;
+            }
+            Some(FrameKind::Array { index, .. }) => {
+                *index += 1;
+                self.element_started();
+            }
+            Some(FrameKind::Map { key, .. }) => *key = None,
+            Some(FrameKind::Enum { .. }) | None => {}
+        }
+    }
+}
+
+impl Default for KdlSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Serializer for KdlSerializer {
@@ -63,14 +632,7 @@ impl Serializer for KdlSerializer {
 
     fn serialize_bool(&mut self, v: bool) -> Result<(), Self::Error> {
         log::trace!("Serializing bool: {}", v);
-        if let Some(ref mut node) = self.current_node {
-            if let Some(key) = self.current_key.take() {
-                node.push(KdlEntry::new_prop(key, KdlValue::Bool(v)));
-            } else {
-                node.push(KdlEntry::new(KdlValue::Bool(v)));
-            }
-        }
-        Ok(())
+        self.push_value(KdlValue::Bool(v))
     }
 
     fn serialize_i8(&mut self, v: i8) -> Result<(), Self::Error> {
@@ -87,26 +649,12 @@ impl Serializer for KdlSerializer {
 
     fn serialize_i64(&mut self, v: i64) -> Result<(), Self::Error> {
         log::trace!("Serializing i64: {}", v);
-        if let Some(ref mut node) = self.current_node {
-            if let Some(key) = self.current_key.take() {
-                node.push(KdlEntry::new_prop(key, KdlValue::Integer(v as i128)));
-            } else {
-                node.push(KdlEntry::new(KdlValue::Integer(v as i128)));
-            }
-        }
-        Ok(())
+        self.push_value(KdlValue::Integer(v as i128))
     }
 
     fn serialize_i128(&mut self, v: i128) -> Result<(), Self::Error> {
         log::trace!("Serializing i128: {}", v);
-        if let Some(ref mut node) = self.current_node {
-            if let Some(key) = self.current_key.take() {
-                node.push(KdlEntry::new_prop(key, KdlValue::Integer(v)));
-            } else {
-                node.push(KdlEntry::new(KdlValue::Integer(v)));
-            }
-        }
-        Ok(())
+        self.push_value(KdlValue::Integer(v))
     }
 
     fn serialize_u8(&mut self, v: u8) -> Result<(), Self::Error> {
@@ -122,23 +670,14 @@ impl Serializer for KdlSerializer {
     }
 
     fn serialize_u64(&mut self, v: u64) -> Result<(), Self::Error> {
-        if v > i128::MAX as u64 {
-            return Err(KdlSerializeError::new(format!(
-                "u64 value {} is too large for KDL",
-                v
-            )));
-        }
         self.serialize_i128(v as i128)
     }
 
     fn serialize_u128(&mut self, v: u128) -> Result<(), Self::Error> {
-        if v > i128::MAX as u128 {
-            return Err(KdlSerializeError::new(format!(
-                "u128 value {} is too large for KDL",
-                v
-            )));
+        match i128::try_from(v) {
+            Ok(v) => self.serialize_i128(v),
+            Err(_) => Err(self.error(KdlSerializeErrorKind::OutOfRange(format!("u128 value {v}")))),
         }
-        self.serialize_i128(v as i128)
     }
 
     fn serialize_f32(&mut self, v: f32) -> Result<(), Self::Error> {
@@ -147,14 +686,7 @@ impl Serializer for KdlSerializer {
 
     fn serialize_f64(&mut self, v: f64) -> Result<(), Self::Error> {
         log::trace!("Serializing f64: {}", v);
-        if let Some(ref mut node) = self.current_node {
-            if let Some(key) = self.current_key.take() {
-                node.push(KdlEntry::new_prop(key, KdlValue::Float(v)));
-            } else {
-                node.push(KdlEntry::new(KdlValue::Float(v)));
-            }
-        }
-        Ok(())
+        self.push_value(KdlValue::Float(v))
     }
 
     fn serialize_char(&mut self, v: char) -> Result<(), Self::Error> {
@@ -163,61 +695,146 @@ impl Serializer for KdlSerializer {
 
     fn serialize_str(&mut self, v: &str) -> Result<(), Self::Error> {
         log::trace!("Serializing string: {}", v);
-        if let Some(ref mut node) = self.current_node {
-            if let Some(key) = self.current_key.take() {
-                node.push(KdlEntry::new_prop(key, KdlValue::String(v.to_string())));
-            } else {
-                node.push(KdlEntry::new(KdlValue::String(v.to_string())));
-            }
-        }
-        Ok(())
+        self.push_value(KdlValue::String(v.to_string()))
     }
 
     fn serialize_bytes(&mut self, _v: &[u8]) -> Result<(), Self::Error> {
         // KDL doesn't have native byte array support
-        Err(KdlSerializeError::new("Byte arrays not supported in KDL"))
+        Err(self.error(KdlSerializeErrorKind::UnsupportedType("byte arrays")))
     }
 
     fn serialize_none(&mut self) -> Result<(), Self::Error> {
         log::trace!("Serializing None");
-        if let Some(ref mut node) = self.current_node {
-            if let Some(key) = self.current_key.take() {
-                node.push(KdlEntry::new_prop(key, KdlValue::Null));
-            } else {
-                node.push(KdlEntry::new(KdlValue::Null));
-            }
-        }
-        Ok(())
+        self.push_value(KdlValue::Null)
     }
 
     fn start_some(&mut self) -> Result<(), Self::Error> {
         log::trace!("Starting Some");
         // For Option<T>, we just serialize the inner value
+        if let Some(Def::Option(option_def)) = self.next_shape.map(|shape| &shape.def) {
+            self.next_shape = Some(option_def.t());
+        }
         Ok(())
     }
 
     fn serialize_unit(&mut self) -> Result<(), Self::Error> {
         log::trace!("Serializing unit");
+        self.value_done();
         Ok(())
     }
 
     fn serialize_unit_variant(
         &mut self,
-        _variant_index: usize,
+        variant_index: usize,
         variant: &'static str,
     ) -> Result<(), Self::Error> {
         log::trace!("Serializing unit variant: {}", variant);
+        let discriminant = self
+            .next_shape
+            .and_then(|shape| enum_variants(shape).get(variant_index))
+            .and_then(|variant| variant.discriminant);
+        if let Some(discriminant) = discriminant.filter(|_| {
+            self.owning_field()
+                .is_some_and(|field| has_kdl_attr(field, "discriminant"))
+        }) {
+            // `#[facet(discriminant)]` fields are written as numeric codes, like `priority 3`
+            return self.push_value(KdlValue::Integer(discriminant.into()));
+        }
+        if self.next_shape.is_some_and(has_data_variants) {
+            // Alongside variants holding data, unit variants are bare nodes, like `stdio`, or nodes that are just
+            // marked with the variant, like `(stdio)listener`
+            let tagging = self.tagging();
+            if tagging != Tagging::External {
+                self.open_tagged_node(tagging, variant)?;
+                self.close_node();
+                self.next_shape = None;
+                self.value_done();
+                return Ok(());
+            }
+            let node = KdlNode::new(variant);
+            match self.take_enum_wrapper() {
+                Some(name) => {
+                    let mut wrapper = KdlNode::new(name);
+                    wrapper.ensure_children().nodes_mut().push(node);
+                    self.attach_node(wrapper);
+                }
+                None => self.attach_node(node),
+            }
+            self.next_shape = None;
+            self.value_done();
+            return Ok(());
+        }
         self.serialize_str(variant)
     }
 
     fn start_object(&mut self, _len: Option<usize>) -> Result<(), Self::Error> {
         log::trace!("Starting object");
+        if self.next_shape.is_some_and(has_data_variants) {
+            // The variant's node is started once its name is known, in `serialize_field_name`
+            let tagging = self.tagging();
+            let wrapper = if tagging == Tagging::External {
+                self.take_enum_wrapper()
+            } else {
+                None
+            };
+            let kind = FrameKind::Enum {
+                variant: None,
+                tagging,
+            };
+            return self.push_frame(kind, "an enum", wrapper);
+        }
+
+        let fields = match self.frames.last() {
+            Some(Frame {
+                kind:
+                    FrameKind::Enum {
+                        variant: Some(variant),
+                        ..
+                    },
+                ..
+            }) if matches!(variant.data.kind, StructKind::Struct) => variant.data.fields,
+            _ => self.next_shape.map_or(&[][..], struct_fields),
+        };
         // Objects in KDL are represented as nodes with children
+        let node_name = self.take_node_name(false);
+        self.push_frame(
+            FrameKind::Object {
+                fields,
+                field_name: None,
+                field: None,
+            },
+            "a struct",
+            node_name,
+        )
+    }
+
+    fn end_object(&mut self) -> Result<(), Self::Error> {
+        self.pop_frame();
         Ok(())
     }
 
     fn serialize_field_name(&mut self, name: &'static str) -> Result<(), Self::Error> {
         log::trace!("Serializing field name: {}", name);
+        match self.frames.last_mut() {
+            Some(Frame {
+                kind: FrameKind::Enum { .. },
+                ..
+            }) => return self.start_variant(name),
+            Some(Frame {
+                kind:
+                    FrameKind::Object {
+                        fields,
+                        field_name,
+                        field,
+                    },
+                ..
+            }) => {
+                *field = fields.iter().find(|field| field.name == name);
+                *field_name = Some(name);
+                self.next_shape = field.map(|field| field.shape());
+            }
+            _ => {}
+        }
         // Store the field name for the next value
         self.current_key = Some(name.to_string());
         Ok(())
@@ -225,25 +842,159 @@ impl Serializer for KdlSerializer {
 
     fn start_array(&mut self, _len: Option<usize>) -> Result<(), Self::Error> {
         log::trace!("Starting array");
-        // Arrays in KDL are represented as multiple arguments
+        // Arrays in KDL are represented as multiple arguments, except at the top level, in fields that aren't
+        // properties or arguments, and for enums holding data, where each element is a node of its own. Elements of
+        // `children` fields are named after the field's filter or their type, and those of other fields after the
+        // field. Fixed-size arrays outside of properties and arguments are a single node instead.
+        let item_node = if self.frames.is_empty() && self.current_node.is_none() {
+            Some(self.default_item_node())
+        } else if self
+            .current_field()
+            .is_some_and(|field| has_kdl_attr(field, "arguments"))
+        {
+            self.current_key = None;
+            None
+        } else if let Some(children) = self
+            .current_field()
+            .and_then(|field| kdl_attr(field, "children"))
+        {
+            self.current_key = None;
+            Some(children.map_or_else(|| self.default_item_node(), str::to_owned))
+        } else if self
+            .next_shape
+            .is_some_and(|shape| matches!(shape.def, Def::Array(_)))
+            && !self.current_field().is_some_and(|field| {
+                has_kdl_attr(field, "property") || has_kdl_attr(field, "argument")
+            })
+        {
+            // Fixed-size arrays are a single node with an argument per element, like `rgb 255 128 0`
+            let node_name = self.take_node_name(false);
+            return self.push_frame(
+                FrameKind::Array {
+                    index: 0,
+                    item_node: None,
+                },
+                "a list",
+                node_name,
+            );
+        } else if self.current_field().is_some_and(|field| {
+            !has_kdl_attr(field, "property") && !has_kdl_attr(field, "argument")
+        }) {
+            self.current_key.take()
+        } else if self
+            .next_shape
+            .and_then(element_shape)
+            .is_some_and(has_data_variants)
+        {
+            // Elements of enums holding data are nodes named after their variant, like the steps of `parallel { ... }`
+            Some(self.default_item_node())
+        } else {
+            None
+        };
+        self.push_frame(
+            FrameKind::Array {
+                index: 0,
+                item_node,
+            },
+            "a list",
+            None,
+        )
+    }
+
+    fn end_array(&mut self) -> Result<(), Self::Error> {
+        self.pop_frame();
         Ok(())
     }
 
     fn start_map(&mut self, _len: Option<usize>) -> Result<(), Self::Error> {
         log::trace!("Starting map");
-        // Maps in KDL are represented as properties
+        // Maps in KDL are represented as a node with a child for each entry, or as properties of the current node for
+        // `properties` fields
+        let as_properties = self
+            .current_field()
+            .is_some_and(|field| has_kdl_attr(field, "properties"));
+        let node_name = if as_properties {
+            self.current_key = None;
+            None
+        } else {
+            self.take_node_name(false)
+        };
+        self.push_frame(
+            FrameKind::Map {
+                key: None,
+                as_properties,
+            },
+            "a map",
+            node_name,
+        )
+    }
+
+    fn end_map(&mut self) -> Result<(), Self::Error> {
+        self.pop_frame();
         Ok(())
     }
 }
 
+/// The type of the elements of `shape`, if it's a list, array or set.
+fn element_shape(shape: &'static Shape) -> Option<&'static Shape> {
+    match &shape.def {
+        Def::List(list_def) => Some(list_def.t()),
+        Def::Array(array_def) => Some(array_def.t()),
+        Def::Set(set_def) => Some(set_def.t()),
+        _ => None,
+    }
+}
+
+/// Whether values of `shape` are written as a single KDL value, like `30` or `"fast"`, rather than as a node.
+fn is_value(shape: &'static Shape) -> bool {
+    !matches!(shape.ty, Type::User(UserType::Struct(_)))
+        && element_shape(shape).is_none()
+        && !matches!(shape.def, Def::Map(_))
+        && !has_data_variants(shape)
+}
+
+/// Whether values of `shape` can be written as a whole document, which is a struct with nothing but child nodes.
+fn is_document(shape: &'static Shape) -> bool {
+    matches!(shape.ty, Type::User(UserType::Struct(_)))
+        && positional_len(shape).is_none()
+        && struct_fields(shape).iter().all(|field| {
+            ["argument", "arguments", "property", "properties"]
+                .iter()
+                .all(|attr| !has_kdl_attr(field, attr))
+        })
+}
+
+/// The name given to nodes holding values of `shape` when nothing else names them, like `http-route` for `HttpRoute`.
+fn default_node_name(shape: &'static Shape) -> String {
+    let mut name = String::new();
+    let mut previous: Option<char> = None;
+    for c in shape.type_identifier.chars() {
+        if c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()) {
+            name.push('-');
+        }
+        name.extend(c.to_lowercase());
+        previous = Some(c);
+    }
+    name
+}
+
 /// Serialize a value to a KDL string using facet-serialize.
+///
+/// Structs are written as a document with a node per field, like [`from_str`](crate::from_str) reads them, and lists
+/// are written as one top-level node per element, named after the type of the elements (so a `Vec<HttpRoute>` becomes
+/// a series of `http-route` nodes). Use [`to_string_nodes`] to pick the name of those nodes.
+///
+/// A document can't have arguments or properties of its own, so structs with `argument`, `arguments`, `property` or
+/// `properties` fields are written as a single `root` node instead, which `from_str` can't read back.
 pub fn to_string<'a, T>(value: &'a T) -> Result<String, KdlSerializeError>
 where
-    T: Serialize<'a>,
+    T: Facet<'a> + Serialize<'a>,
 {
     let mut serializer = KdlSerializer::new();
-    // For now, we'll create a root node for the serialization
-    serializer.current_node = Some(KdlNode::new("root"));
+    if element_shape(T::SHAPE).is_none() && !is_document(T::SHAPE) {
+        serializer.current_node = Some(KdlNode::new("root"));
+    }
+    serializer.next_shape = Some(T::SHAPE);
     value.serialize(&mut serializer)?;
 
     // Add the root node to the document
@@ -253,3 +1004,27 @@ where
 
     Ok(serializer.into_string())
 }
+
+/// Serialize a list of values to a KDL string, as one top-level node called `node_name` per value.
+///
+/// This is the inverse of [`from_str_nodes`](crate::from_str_nodes).
+pub fn to_string_nodes<'a, T>(values: &'a [T], node_name: &str) -> Result<String, KdlSerializeError>
+where
+    T: Facet<'a> + Serialize<'a>,
+{
+    let mut serializer = KdlSerializer::new();
+    serializer.frames.push(Frame {
+        shape: None,
+        kind: FrameKind::Array {
+            index: 0,
+            item_node: Some(node_name.to_owned()),
+        },
+        nodes: 0,
+    });
+    for value in values {
+        serializer.next_shape = Some(T::SHAPE);
+        value.serialize(&mut serializer)?;
+    }
+
+    Ok(serializer.into_string())
+}
@@ -0,0 +1,42 @@
+//! "Did you mean...?" suggestions for names that don't match anything.
+
+/// The candidates that `name` could plausibly be a typo of, closest first.
+pub(crate) fn suggestions<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    // NOTE: Allow roughly one typo for every three characters, so short names don't match everything
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut suggestions: Vec<_> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .collect();
+    suggestions.sort_by_key(|&(distance, _)| distance);
+
+    suggestions
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .take(3)
+        .collect()
+}
+
+/// The Levenshtein distance between `a` and `b`: how many single-character insertions, deletions or substitutions it
+/// takes to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
@@ -0,0 +1,88 @@
+use facet::Facet;
+use indoc::indoc;
+
+#[derive(Debug, Facet)]
+struct Config {
+    #[facet(child)]
+    server: Server,
+    #[facet(child)]
+    database: Database,
+}
+
+#[derive(Debug, Facet)]
+struct Server {
+    #[facet(property)]
+    host: String,
+    #[facet(property)]
+    port: u16,
+}
+
+#[derive(Debug, Facet)]
+struct Database {
+    #[facet(property)]
+    url: String,
+    #[facet(property)]
+    pool: u32,
+}
+
+#[test]
+fn valid_document_has_no_errors() {
+    let kdl = indoc! {r#"
+        server host="localhost" port=8080
+        database url="postgres://localhost" pool=4
+    "#};
+
+    let config: Config = facet_kdl::from_str_all_errors(kdl).unwrap();
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.database.pool, 4);
+}
+
+#[test]
+fn every_error_is_reported() {
+    let kdl = indoc! {r#"
+        server host="localhost" port="eighty"
+        database url=5 pool=-1
+    "#};
+
+    let errors = facet_kdl::from_str_all_errors::<Config>(kdl).unwrap_err();
+    let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    for message in &messages {
+        println!("{message}\n");
+    }
+
+    assert_eq!(errors.len(), 3);
+    assert!(messages[0].contains("in `server.port`"));
+    assert!(messages[0].contains("--> line 1, column"));
+    assert!(messages[1].contains("in `database.url`"));
+    assert!(messages[1].contains("--> line 2, column"));
+    assert!(messages[2].contains("in `database.pool`"));
+}
+
+#[test]
+fn parse_errors_are_reported_alone() {
+    let kdl = indoc! {r#"
+        server host="localhost" {
+    "#};
+
+    let errors = facet_kdl::from_str_all_errors::<Config>(kdl).unwrap_err();
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn missing_fields_are_reported_alongside_other_errors() {
+    let kdl = indoc! {r#"
+        server port="eighty"
+        database url="postgres://localhost"
+    "#};
+
+    let errors = facet_kdl::from_str_all_errors::<Config>(kdl).unwrap_err();
+    let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    for message in &messages {
+        println!("{message}\n");
+    }
+
+    assert_eq!(errors.len(), 3);
+    assert!(messages[0].contains("in `server.port`"));
+    assert!(messages[1].starts_with("missing property `host` of `server`"));
+    assert!(messages[2].starts_with("missing property `pool` of `database`"));
+}
@@ -0,0 +1,108 @@
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+enum Figure {
+    Circle {
+        #[facet(property)]
+        r: f64,
+    },
+    Rect {
+        #[facet(property)]
+        w: f64,
+        #[facet(property)]
+        h: f64,
+    },
+    Dot,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+enum Duration {
+    Seconds(u64),
+    Millis(u64),
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Canvas {
+    #[facet(argument)]
+    name: String,
+    #[facet(property, annotated)]
+    timeout: Duration,
+    #[facet(child, annotated)]
+    background: Figure,
+    #[facet(children = "shape", annotated)]
+    shapes: Vec<Figure>,
+}
+
+#[test]
+fn annotations_select_variants() {
+    let kdl = indoc! {r#"
+        canvas "demo" timeout=(millis)250 {
+            (rect)background w=640.0 h=480.0
+            (circle)shape r=1.5
+            (dot)shape
+        }
+    "#};
+
+    let canvases: Vec<Canvas> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        canvases,
+        [Canvas {
+            name: "demo".to_owned(),
+            timeout: Duration::Millis(250),
+            background: Figure::Rect { w: 640.0, h: 480.0 },
+            shapes: vec![Figure::Circle { r: 1.5 }, Figure::Dot],
+        }]
+    );
+}
+
+#[test]
+fn annotated_values_can_be_arguments_of_child_nodes() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Job {
+        #[facet(child, annotated)]
+        timeout: Duration,
+    }
+
+    let job: Job = facet_kdl::from_str("timeout (seconds)30").unwrap();
+    assert_eq!(job.timeout, Duration::Seconds(30));
+}
+
+#[test]
+fn unknown_annotations_list_the_variants() {
+    let kdl = indoc! {r#"
+        canvas "demo" timeout=(minutes)1 {
+            (dot)background
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Vec<Canvas>>(kdl).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::UnknownVariant { name, expected, .. } => {
+            assert_eq!(name, "minutes");
+            assert_eq!(expected, &["seconds", "millis"]);
+        }
+        other => panic!("expected an unknown variant, got {other:?}"),
+    }
+}
+
+#[test]
+fn annotations_round_trip() {
+    let canvases = vec![Canvas {
+        name: "demo".to_owned(),
+        timeout: Duration::Seconds(5),
+        background: Figure::Dot,
+        shapes: vec![Figure::Rect { w: 2.0, h: 3.0 }, Figure::Circle { r: 1.0 }],
+    }];
+
+    let kdl = facet_kdl::to_string(&canvases).unwrap();
+    assert!(kdl.contains("timeout=(seconds)5"), "{kdl}");
+    assert!(kdl.contains("(dot)background"), "{kdl}");
+    assert!(kdl.contains("(rect)shape w=2.0 h=3.0"), "{kdl}");
+    assert_eq!(facet_kdl::from_str::<Vec<Canvas>>(&kdl).unwrap(), canvases);
+}
@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Config {
+    #[facet(child)]
+    process: Process,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Process {
+    #[facet(argument)]
+    program: String,
+    #[facet(arguments)]
+    args: Vec<String>,
+    #[facet(property, default)]
+    restart: bool,
+}
+
+#[test]
+fn remaining_arguments_are_collected() {
+    let kdl = r#"process "node" "server.js" "--port" "3000" restart=#true"#;
+
+    let config: Config = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        config.process,
+        Process {
+            program: "node".to_owned(),
+            args: vec![
+                "server.js".to_owned(),
+                "--port".to_owned(),
+                "3000".to_owned()
+            ],
+            restart: true,
+        }
+    );
+}
+
+#[test]
+fn no_remaining_arguments_is_empty() {
+    let config: Config = facet_kdl::from_str(r#"process "true""#).unwrap();
+    assert!(config.process.args.is_empty());
+}
+
+#[test]
+fn arguments_fill_sets_and_arrays() {
+    #[derive(Debug, Facet)]
+    struct Tags {
+        #[facet(child)]
+        tags: TagSet,
+        #[facet(child)]
+        origin: Origin,
+    }
+
+    #[derive(Debug, Facet)]
+    struct TagSet {
+        #[facet(arguments)]
+        names: HashSet<String>,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Origin {
+        #[facet(arguments)]
+        xyz: [f64; 3],
+    }
+
+    let kdl = indoc! {r#"
+        tags "web" "api" "web"
+        origin 1.0 2 -3.5
+    "#};
+
+    let tags: Tags = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        tags.tags.names,
+        HashSet::from(["web".to_owned(), "api".to_owned()])
+    );
+    assert_eq!(tags.origin.xyz, [1.0, 2.0, -3.5]);
+
+    let error = facet_kdl::from_str::<Tags>(r#"tags; origin 1.0 2.0"#).unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        KdlErrorKind::Arity {
+            expected: 3,
+            found: 2,
+            ..
+        }
+    ));
+    assert_eq!(
+        error.to_string().lines().next().unwrap(),
+        "`xyz` of `Origin` takes 3 arguments, but found 2"
+    );
+}
+
+#[test]
+fn errors_point_at_the_argument() {
+    #[derive(Debug, Facet)]
+    struct Ports {
+        #[facet(child)]
+        listen: Listen,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Listen {
+        #[facet(arguments)]
+        ports: Vec<u16>,
+    }
+
+    let error = facet_kdl::from_str::<Ports>(r#"listen 80 443 "http""#).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "listen.ports[2]");
+    assert!(
+        error
+            .to_string()
+            .contains("argument #2 (`ports`) of `Listen`")
+    );
+}
+
+#[test]
+fn arguments_serialize_as_arguments() {
+    let config = Config {
+        process: Process {
+            program: "node".to_owned(),
+            args: vec!["server.js".to_owned(), "--inspect".to_owned()],
+            restart: false,
+        },
+    };
+
+    let kdl = facet_kdl::to_string(&config).unwrap();
+    assert!(
+        kdl.contains(r#"process "node" "server.js" "--inspect" restart=#false"#),
+        "{kdl}"
+    );
+}
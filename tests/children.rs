@@ -0,0 +1,116 @@
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Router {
+    #[facet(child)]
+    prefix: String,
+    #[facet(children = "route")]
+    routes: Vec<Route>,
+    #[facet(children)]
+    middleware: Vec<Middleware>,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Route {
+    #[facet(argument)]
+    path: String,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Middleware {
+    #[facet(property, default)]
+    enabled: bool,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Block {
+    #[facet(children)]
+    items: Vec<Route>,
+}
+
+#[test]
+fn children_are_collected_in_order() {
+    let kdl = indoc! {r#"
+        route "/"
+        logger enabled=#true
+        prefix "/api"
+        route "/users"
+        cors
+        route "/posts"
+    "#};
+
+    let router: Router = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(router.prefix, "/api");
+    assert_eq!(
+        router.routes,
+        ["/", "/users", "/posts"].map(|path| Route {
+            path: path.to_owned()
+        })
+    );
+    assert_eq!(
+        router.middleware,
+        [Middleware { enabled: true }, Middleware { enabled: false }]
+    );
+}
+
+#[test]
+fn unfiltered_children_take_every_node() {
+    let kdl = indoc! {r#"
+        a "/a"
+        b "/b"
+        a "/c"
+    "#};
+
+    let block: Block = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        block
+            .items
+            .iter()
+            .map(|route| &*route.path)
+            .collect::<Vec<_>>(),
+        ["/a", "/b", "/c"]
+    );
+}
+
+#[test]
+fn missing_children_are_empty() {
+    let router: Router = facet_kdl::from_str(r#"prefix "/""#).unwrap();
+    assert!(router.routes.is_empty());
+    assert!(router.middleware.is_empty());
+}
+
+#[test]
+fn filtered_children_leave_other_nodes_alone() {
+    #[derive(Debug, Facet)]
+    struct Routes {
+        #[facet(children = "route")]
+        routes: Vec<Route>,
+    }
+
+    let error = facet_kdl::from_str::<Routes>(r#"route "/"; rout "/oops""#).unwrap_err();
+    assert!(matches!(error.kind(), KdlErrorKind::UnknownField { .. }));
+    assert_eq!(error.path().unwrap().to_string(), "rout");
+}
+
+#[test]
+fn errors_point_at_the_child() {
+    let error = facet_kdl::from_str::<Block>(r#"a "/a"; b 5"#).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "items[1].path");
+}
+
+#[test]
+fn children_serialize_as_nodes() {
+    let router = Router {
+        prefix: "/api".to_owned(),
+        routes: vec![Route {
+            path: "/users".to_owned(),
+        }],
+        middleware: vec![Middleware { enabled: true }],
+    };
+
+    let kdl = facet_kdl::to_string(&router).unwrap();
+    assert!(kdl.contains(r#"route "/users""#), "{kdl}");
+    assert!(kdl.contains("middleware enabled=#true"), "{kdl}");
+}
@@ -69,10 +69,11 @@ fn test_process_list() {
             assert_eq!(result.process.len(), 2);
             assert_eq!(result.process[0].id, "web-server");
             assert_eq!(result.process[1].id, "redis");
+            assert_eq!(result.process[1].command, "/usr/local/bin/redis-server");
         }
         Err(e) => {
             println!("Parse error: {:?}", e);
-            // エラーを詳しく見る
+            panic!("Failed to parse");
         }
     }
 }
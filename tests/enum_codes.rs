@@ -0,0 +1,141 @@
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "lowercase")]
+enum Priority {
+    Low = 1,
+    Normal = 3,
+    Urgent = 7,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Message {
+    #[facet(argument)]
+    topic: String,
+    #[facet(child)]
+    priority: Priority,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct CodedMessage {
+    #[facet(argument)]
+    topic: String,
+    #[facet(child, discriminant)]
+    priority: Priority,
+    #[facet(property, discriminant)]
+    fallback: Priority,
+}
+
+#[test]
+fn integers_select_variants_by_discriminant() {
+    let kdl = indoc! {r#"
+        message "alerts" {
+            priority 7
+        }
+        message "digest" {
+            priority "low"
+        }
+    "#};
+
+    let messages: Vec<Message> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(messages[0].priority, Priority::Urgent);
+    assert_eq!(messages[1].priority, Priority::Low);
+}
+
+#[test]
+fn unknown_codes_list_the_valid_ones() {
+    let kdl = indoc! {r#"
+        message "alerts" {
+            priority 2
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Vec<Message>>(kdl).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::UnknownDiscriminant {
+            value, expected, ..
+        } => {
+            assert_eq!(*value, 2);
+            assert_eq!(expected, &[1, 3, 7]);
+        }
+        other => panic!("expected an unknown code, got {other:?}"),
+    }
+    assert!(
+        error
+            .to_string()
+            .contains("expected one of `1`, `3`, or `7`"),
+        "{error}"
+    );
+}
+
+#[test]
+fn variants_are_written_by_name_by_default() {
+    let messages = vec![Message {
+        topic: "alerts".to_owned(),
+        priority: Priority::Urgent,
+    }];
+
+    let kdl = facet_kdl::to_string(&messages).unwrap();
+    assert!(kdl.contains(r#"priority "urgent""#), "{kdl}");
+}
+
+#[test]
+fn discriminant_fields_are_written_as_codes() {
+    let messages = vec![CodedMessage {
+        topic: "alerts".to_owned(),
+        priority: Priority::Urgent,
+        fallback: Priority::Normal,
+    }];
+
+    let kdl = facet_kdl::to_string(&messages).unwrap();
+    assert!(kdl.contains("fallback=3"), "{kdl}");
+    assert!(kdl.contains("priority 7"), "{kdl}");
+    assert_eq!(
+        facet_kdl::from_str::<Vec<CodedMessage>>(&kdl).unwrap(),
+        messages
+    );
+}
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "lowercase")]
+enum Mode {
+    Fast,
+    Slow,
+}
+
+#[test]
+fn implicit_discriminants_are_not_codes() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Job {
+        #[facet(child)]
+        mode: Mode,
+    }
+
+    let error = facet_kdl::from_str::<Job>("mode 0").unwrap_err();
+    assert!(
+        matches!(error.kind(), KdlErrorKind::Mismatch { .. }),
+        "{error}"
+    );
+    assert_eq!(
+        facet_kdl::from_str::<Job>(r#"mode "slow""#).unwrap().mode,
+        Mode::Slow
+    );
+}
+
+#[test]
+fn discriminant_fields_accept_implicit_codes() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Job {
+        #[facet(child, discriminant)]
+        mode: Mode,
+    }
+
+    assert_eq!(
+        facet_kdl::from_str::<Job>("mode 1").unwrap().mode,
+        Mode::Slow
+    );
+}
@@ -0,0 +1,125 @@
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+enum Step {
+    Run(String),
+    Copy {
+        #[facet(property)]
+        from: String,
+        #[facet(property)]
+        to: String,
+    },
+    Parallel(Vec<Step>),
+    Checkpoint,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Pipeline {
+    #[facet(argument)]
+    name: String,
+    steps: Vec<Step>,
+}
+
+#[test]
+fn mixed_steps_keep_document_order() {
+    let kdl = indoc! {r#"
+        pipeline "build" {
+            run "make"
+            copy from="target/app" to="dist/app"
+            checkpoint
+            run "make test"
+        }
+    "#};
+
+    let pipelines: Vec<Pipeline> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        pipelines[0].steps,
+        [
+            Step::Run("make".to_owned()),
+            Step::Copy {
+                from: "target/app".to_owned(),
+                to: "dist/app".to_owned(),
+            },
+            Step::Checkpoint,
+            Step::Run("make test".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn variants_can_nest_steps() {
+    let kdl = indoc! {r#"
+        pipeline "release" {
+            run "make"
+            parallel {
+                run "make docs"
+                parallel {
+                    run "make deb"
+                    run "make rpm"
+                }
+            }
+        }
+    "#};
+
+    let pipelines: Vec<Pipeline> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        pipelines[0].steps,
+        [
+            Step::Run("make".to_owned()),
+            Step::Parallel(vec![
+                Step::Run("make docs".to_owned()),
+                Step::Parallel(vec![
+                    Step::Run("make deb".to_owned()),
+                    Step::Run("make rpm".to_owned()),
+                ]),
+            ]),
+        ]
+    );
+}
+
+#[test]
+fn errors_point_at_the_nested_step() {
+    let kdl = indoc! {r#"
+        pipeline "release" {
+            parallel {
+                run "make docs"
+                sleep 5
+            }
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Vec<Pipeline>>(kdl).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::UnknownVariant { name, expected, .. } => {
+            assert_eq!(name, "sleep");
+            assert!(expected.contains(&"run"), "{expected:?}");
+        }
+        other => panic!("expected an unknown variant, got {other:?}"),
+    }
+    assert_eq!(error.path().unwrap().to_string(), "[0].steps[0].0[1]");
+}
+
+#[test]
+fn mixed_steps_round_trip() {
+    let pipelines = vec![Pipeline {
+        name: "release".to_owned(),
+        steps: vec![
+            Step::Copy {
+                from: "a".to_owned(),
+                to: "b".to_owned(),
+            },
+            Step::Parallel(vec![Step::Run("make".to_owned()), Step::Checkpoint]),
+        ],
+    }];
+
+    let kdl = facet_kdl::to_string(&pipelines).unwrap();
+    assert!(kdl.contains(r#"copy from="a" to="b""#), "{kdl}");
+    assert_eq!(
+        facet_kdl::from_str::<Vec<Pipeline>>(&kdl).unwrap(),
+        pipelines
+    );
+}
@@ -0,0 +1,115 @@
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+enum Listener {
+    Tcp {
+        #[facet(property)]
+        port: u16,
+        #[facet(property, default)]
+        host: Option<String>,
+    },
+    Unix(String),
+    Stdio,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Server {
+    #[facet(argument)]
+    name: String,
+    #[facet(children)]
+    listeners: Vec<Listener>,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Config {
+    #[facet(child)]
+    listener: Listener,
+}
+
+#[test]
+fn node_names_select_variants() {
+    let kdl = indoc! {r#"
+        server "api" {
+            tcp port=80
+            unix "/run/api.sock"
+            stdio
+        }
+    "#};
+
+    let servers: Vec<Server> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        servers[0].listeners,
+        [
+            Listener::Tcp {
+                port: 80,
+                host: None
+            },
+            Listener::Unix("/run/api.sock".to_owned()),
+            Listener::Stdio,
+        ]
+    );
+}
+
+#[test]
+fn single_fields_wrap_the_variant_node() {
+    let config: Config = facet_kdl::from_str("listener { tcp port=8080; }").unwrap();
+    assert_eq!(
+        config.listener,
+        Listener::Tcp {
+            port: 8080,
+            host: None
+        }
+    );
+
+    let config: Config = facet_kdl::from_str("listener { stdio; }").unwrap();
+    assert_eq!(config.listener, Listener::Stdio);
+}
+
+#[test]
+fn unknown_variants_list_the_expected_ones() {
+    let kdl = indoc! {r#"
+        server "api" {
+            udp port=53
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Vec<Server>>(kdl).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::UnknownVariant { name, expected, .. } => {
+            assert_eq!(name, "udp");
+            assert_eq!(expected, &["tcp", "unix", "stdio"]);
+        }
+        other => panic!("expected an unknown variant, got {other:?}"),
+    }
+}
+
+#[test]
+fn enums_round_trip() {
+    let servers = vec![Server {
+        name: "api".to_owned(),
+        listeners: vec![
+            Listener::Tcp {
+                port: 80,
+                host: Some("localhost".to_owned()),
+            },
+            Listener::Unix("/run/api.sock".to_owned()),
+            Listener::Stdio,
+        ],
+    }];
+
+    let kdl = facet_kdl::to_string(&servers).unwrap();
+    assert!(kdl.contains(r#"tcp port=80 host="localhost""#), "{kdl}");
+    assert!(kdl.contains(r#"unix "/run/api.sock""#), "{kdl}");
+    assert_eq!(facet_kdl::from_str::<Vec<Server>>(&kdl).unwrap(), servers);
+
+    let config = Config {
+        listener: Listener::Unix("/run/api.sock".to_owned()),
+    };
+    let kdl = facet_kdl::to_string(&config).unwrap();
+    assert!(!kdl.contains("root"), "{kdl}");
+    assert_eq!(facet_kdl::from_str::<Config>(&kdl).unwrap(), config);
+}
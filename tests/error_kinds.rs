@@ -0,0 +1,69 @@
+use std::error::Error;
+
+use facet::Facet;
+use facet_kdl::{KdlErrorKind, PathSegment, Role};
+
+#[derive(Debug, Facet)]
+struct Root {
+    #[facet(child)]
+    listen: Listen,
+}
+
+#[derive(Debug, Facet)]
+struct Listen {
+    #[facet(property)]
+    port: u16,
+}
+
+#[test]
+fn parse_errors_can_be_told_apart() {
+    let error = facet_kdl::from_str::<Root>("listen port=80 {").unwrap_err();
+
+    assert!(matches!(error.kind(), KdlErrorKind::Parse(_)));
+    assert!(error.source().is_some());
+    assert!(error.path().is_none());
+}
+
+#[test]
+fn schema_errors_expose_their_details() {
+    let kdl = r#"listen port="http""#;
+    let error = facet_kdl::from_str::<Root>(kdl).unwrap_err();
+
+    match error.kind() {
+        KdlErrorKind::Mismatch {
+            target, expected, ..
+        } => {
+            assert_eq!(target.role, Role::Property);
+            assert_eq!(target.field, "port");
+            assert_eq!(expected.type_identifier, "u16");
+        }
+        other => panic!("expected a mismatch, got {other:?}"),
+    }
+
+    assert_eq!(
+        error.path().unwrap().segments(),
+        [
+            PathSegment::Field("listen".to_owned()),
+            PathSegment::Field("port".to_owned())
+        ]
+    );
+
+    let span = error.span().unwrap();
+    assert_eq!(span.line(), 1);
+    assert!(kdl[span.range()].contains(r#""http""#));
+}
+
+#[test]
+fn unknown_fields_are_matchable() {
+    let error = facet_kdl::from_str::<Root>("listen portt=80").unwrap_err();
+
+    match error.kind() {
+        KdlErrorKind::UnknownField {
+            name, suggestions, ..
+        } => {
+            assert_eq!(name, "portt");
+            assert_eq!(suggestions, &["port"]);
+        }
+        other => panic!("expected an unknown field, got {other:?}"),
+    }
+}
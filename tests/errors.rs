@@ -0,0 +1,228 @@
+use facet::Facet;
+use indoc::indoc;
+
+#[derive(Debug, Facet)]
+struct Config {
+    #[facet(argument)]
+    name: String,
+    #[facet(argument)]
+    count: i64,
+}
+
+#[test]
+fn type_mismatch_points_at_entry() {
+    let kdl = indoc! {r#"
+        name "server"
+        count "many"
+    "#};
+
+    let error = facet_kdl::from_str::<Config>(kdl).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(message.contains("--> line 2, column"));
+    assert!(message.contains(r#"2 | count "many""#));
+    assert!(message.contains('^'));
+}
+
+#[test]
+fn parse_error_points_at_problem() {
+    let kdl = indoc! {r#"
+        name "server"
+        count 1 {
+    "#};
+
+    let error = facet_kdl::from_str::<Config>(kdl).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(message.contains("--> line"));
+    assert!(message.contains('^'));
+}
+
+#[test]
+fn error_shows_path_to_value() {
+    #[derive(Debug, Facet)]
+    struct Root {
+        #[facet(child)]
+        server: Server,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Server {
+        #[facet(child)]
+        listen: Listen,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Listen {
+        #[facet(property)]
+        port: u16,
+    }
+
+    let kdl = indoc! {r#"
+        server {
+            listen port="http"
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Root>(kdl).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(message.contains("in `server.listen.port`"));
+    assert!(message.contains("--> line 2, column"));
+}
+
+#[test]
+fn mismatched_property_names_expected_and_found() {
+    #[derive(Debug, Facet)]
+    struct Root {
+        #[facet(child)]
+        listen: Listen,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Listen {
+        #[facet(property)]
+        port: u16,
+    }
+
+    let error = facet_kdl::from_str::<Root>(r#"listen port="http""#).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(
+        message.starts_with(
+            r#"property `port` of `Listen` should be `u16`, but found string `"http"`"#
+        )
+    );
+}
+
+#[test]
+fn mismatched_child_node_describes_node() {
+    let kdl = indoc! {r#"
+        name "server"
+        count 1 2 {
+            extra
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Config>(kdl).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(message.starts_with(
+        "child node `count` of `Config` should have a single `i64` argument, but found node with 2 arguments and 1 child"
+    ));
+}
+
+#[test]
+fn extra_argument_is_reported() {
+    #[derive(Debug, Facet)]
+    struct Root {
+        #[facet(child)]
+        title: Title,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Title {
+        #[facet(argument)]
+        text: String,
+    }
+
+    let error = facet_kdl::from_str::<Root>(r#"title "Hello" "World""#).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(
+        message.starts_with(r#"`Title` has no field for argument #1, but found string `"World"`"#)
+    );
+}
+
+#[test]
+fn every_missing_field_is_reported() {
+    #[derive(Debug, Facet)]
+    struct Root {
+        #[facet(child)]
+        meta: Meta,
+        #[facet(child)]
+        process: Process,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Meta {
+        #[facet(property)]
+        version: String,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Process {
+        #[facet(argument)]
+        id: String,
+        #[facet(property)]
+        command: String,
+        #[facet(child)]
+        meta: Meta,
+    }
+
+    let kdl = indoc! {r#"
+        process
+    "#};
+
+    let error = facet_kdl::from_str::<Root>(kdl).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(message.starts_with(
+        "missing argument #0 of `process`, property `command` of `process`, child node `meta` of `process`"
+    ));
+    assert!(message.contains("in `process`"));
+
+    let kdl = indoc! {r#"
+        process "web" command="/usr/bin/node" {
+            meta version="1.0.0"
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Root>(kdl).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(message.starts_with("missing child node `meta`"));
+}
+
+#[test]
+fn mismatched_child_node_describes_the_expected_layout() {
+    #[derive(Debug, Facet)]
+    struct Service {
+        #[facet(child)]
+        env: std::collections::HashMap<String, String>,
+        #[facet(child)]
+        origin: (f64, f64),
+    }
+
+    let kdl = indoc! {r#"
+        env "production"
+        origin 1.0 2.0
+    "#};
+    let error = facet_kdl::from_str::<Service>(kdl).unwrap_err();
+    assert!(
+        error.to_string().starts_with(
+            "child node `env` of `Service` should have a block of child nodes for the entries of `"
+        ),
+        "{error}"
+    );
+
+    let kdl = indoc! {r#"
+        env
+        origin 1.0 y=2.0
+    "#};
+    let error = facet_kdl::from_str::<Service>(kdl).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("child node `origin` of `Service` should have just 2 arguments for `"),
+        "{error}"
+    );
+}
@@ -0,0 +1,106 @@
+use facet::Facet;
+use facet_kdl::{KdlErrorKind, PathSegment};
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Process {
+    #[facet(argument)]
+    name: String,
+    #[facet(property)]
+    command: String,
+    #[facet(child)]
+    limits: Limits,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Limits {
+    #[facet(property)]
+    memory: u32,
+}
+
+fn processes() -> Vec<Process> {
+    vec![
+        Process {
+            name: "web".to_owned(),
+            command: "node server.js".to_owned(),
+            limits: Limits { memory: 512 },
+        },
+        Process {
+            name: "worker".to_owned(),
+            command: "node worker.js".to_owned(),
+            limits: Limits { memory: 256 },
+        },
+    ]
+}
+
+#[test]
+fn each_node_is_an_element() {
+    let kdl = indoc! {r#"
+        process "web" command="node server.js" {
+            limits memory=512
+        }
+        process "worker" command="node worker.js" {
+            limits memory=256
+        }
+    "#};
+
+    let parsed: Vec<Process> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(parsed, processes());
+
+    let parsed: Vec<Process> = facet_kdl::from_str_nodes(kdl, "process").unwrap();
+    assert_eq!(parsed, processes());
+}
+
+#[test]
+fn empty_documents_are_empty_lists() {
+    let parsed: Vec<Process> = facet_kdl::from_str("").unwrap();
+    assert!(parsed.is_empty());
+}
+
+#[test]
+fn node_names_can_be_checked() {
+    let kdl = indoc! {r#"
+        process "web" command="node server.js" { limits memory=512; }
+        service "worker" command="node worker.js" { limits memory=256; }
+    "#};
+
+    // Without a name to check against, any node will do
+    assert!(facet_kdl::from_str::<Vec<Process>>(kdl).is_ok());
+
+    let error = facet_kdl::from_str_nodes::<Process>(kdl, "process").unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        KdlErrorKind::UnexpectedNode { expected, found } if expected == "process" && found == "service"
+    ));
+    assert_eq!(error.path().unwrap().segments(), [PathSegment::Index(1)]);
+}
+
+#[test]
+fn errors_point_at_the_element() {
+    let kdl = indoc! {r#"
+        process "web" command="node server.js" { limits memory=512; }
+        process "worker" command="node worker.js" { limits memory="lots"; }
+    "#};
+
+    let error = facet_kdl::from_str::<Vec<Process>>(kdl).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "[1].limits.memory");
+}
+
+#[test]
+fn lists_round_trip() {
+    let kdl = facet_kdl::to_string(&processes()).unwrap();
+    assert!(kdl.contains("process \"web\""));
+    assert!(!kdl.contains("root"));
+
+    let parsed: Vec<Process> = facet_kdl::from_str_nodes(&kdl, "process").unwrap();
+    assert_eq!(parsed, processes());
+}
+
+#[test]
+fn list_node_names_can_be_chosen() {
+    let kdl = facet_kdl::to_string_nodes(&processes(), "service").unwrap();
+    assert!(kdl.contains("service \"worker\""));
+
+    let parsed: Vec<Process> = facet_kdl::from_str_nodes(&kdl, "service").unwrap();
+    assert_eq!(parsed, processes());
+}
@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Process {
+    #[facet(argument)]
+    id: String,
+    #[facet(property)]
+    command: String,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Supervisor {
+    #[facet(child)]
+    name: String,
+    #[facet(child)]
+    process: Vec<Process>,
+    #[facet(child)]
+    watch: VecDeque<String>,
+    #[facet(child)]
+    port: Box<[u16]>,
+}
+
+#[test]
+fn repeated_nodes_fill_lists() {
+    let kdl = indoc! {r#"
+        process "web" command="node server.js"
+        watch "src"
+        name "app"
+        process "worker" command="node worker.js"
+        port 80
+        watch "static"
+        port 443
+    "#};
+
+    let supervisor: Supervisor = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(supervisor.name, "app");
+    assert_eq!(
+        supervisor.process,
+        [
+            Process {
+                id: "web".to_owned(),
+                command: "node server.js".to_owned(),
+            },
+            Process {
+                id: "worker".to_owned(),
+                command: "node worker.js".to_owned(),
+            },
+        ]
+    );
+    assert_eq!(supervisor.watch, ["src", "static"]);
+    assert_eq!(&*supervisor.port, [80, 443]);
+}
+
+#[test]
+fn missing_lists_are_empty() {
+    let supervisor: Supervisor = facet_kdl::from_str(r#"name "idle""#).unwrap();
+    assert!(supervisor.process.is_empty());
+    assert!(supervisor.watch.is_empty());
+    assert!(supervisor.port.is_empty());
+}
+
+#[test]
+fn errors_point_at_the_element() {
+    let kdl = indoc! {r#"
+        name "app"
+        process "web" command="node server.js"
+        process "worker" command=5
+    "#};
+
+    let error = facet_kdl::from_str::<Supervisor>(kdl).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "process[1].command");
+
+    let errors = facet_kdl::from_str_all_errors::<Supervisor>(indoc! {r#"
+        process "web" command=1
+        process "worker" command=2
+    "#})
+    .unwrap_err();
+    assert_eq!(errors.len(), 3, "{errors:#?}");
+    assert!(
+        errors
+            .iter()
+            .any(|error| matches!(error.kind(), KdlErrorKind::MissingNodes(_))),
+        "{errors:#?}"
+    );
+}
+
+#[test]
+fn lists_serialize_as_repeated_nodes() {
+    let supervisor = Supervisor {
+        name: "app".to_owned(),
+        process: vec![
+            Process {
+                id: "web".to_owned(),
+                command: "node server.js".to_owned(),
+            },
+            Process {
+                id: "worker".to_owned(),
+                command: "node worker.js".to_owned(),
+            },
+        ],
+        watch: VecDeque::from(["src".to_owned()]),
+        port: Box::new([]),
+    };
+
+    let kdl = facet_kdl::to_string(&supervisor).unwrap();
+    assert!(
+        kdl.contains(r#"process "web" command="node server.js""#),
+        "{kdl}"
+    );
+    assert!(
+        kdl.contains(r#"process "worker" command="node worker.js""#),
+        "{kdl}"
+    );
+    assert!(kdl.contains(r#"watch "src""#), "{kdl}");
+}
@@ -0,0 +1,142 @@
+use std::collections::{BTreeMap, HashMap};
+
+use facet::Facet;
+use facet_kdl::KdlSerializeErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Service {
+    #[facet(argument)]
+    name: String,
+    #[facet(child)]
+    env: BTreeMap<String, String>,
+    #[facet(child, default)]
+    upstreams: BTreeMap<String, Upstream>,
+    #[facet(child, default)]
+    ports: BTreeMap<String, BTreeMap<String, u16>>,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Upstream {
+    #[facet(argument)]
+    host: String,
+    #[facet(property)]
+    weight: u8,
+}
+
+#[test]
+fn child_blocks_fill_maps() {
+    let kdl = indoc! {r#"
+        service "web" {
+            env {
+                NODE_ENV "production"
+                PORT "3000"
+            }
+            upstreams {
+                primary "10.0.0.1" weight=3
+                backup "10.0.0.2" weight=1
+            }
+            ports {
+                public {
+                    http 80
+                    https 443
+                }
+                internal {
+                    metrics 9090
+                }
+            }
+        }
+    "#};
+
+    let services: Vec<Service> = facet_kdl::from_str(kdl).unwrap();
+    let service = &services[0];
+    assert_eq!(service.env["NODE_ENV"], "production");
+    assert_eq!(service.env["PORT"], "3000");
+    assert_eq!(
+        service.upstreams["backup"],
+        Upstream {
+            host: "10.0.0.2".to_owned(),
+            weight: 1
+        }
+    );
+    assert_eq!(service.ports["public"]["https"], 443);
+    assert_eq!(service.ports["internal"]["metrics"], 9090);
+}
+
+#[test]
+fn missing_maps_are_empty() {
+    #[derive(Debug, Facet)]
+    struct Env {
+        #[facet(child)]
+        env: HashMap<String, String>,
+    }
+
+    let env: Env = facet_kdl::from_str("").unwrap();
+    assert!(env.env.is_empty());
+
+    let env: Env = facet_kdl::from_str("env").unwrap();
+    assert!(env.env.is_empty());
+}
+
+#[test]
+fn errors_point_at_the_entry() {
+    let kdl = indoc! {r#"
+        service "web" {
+            env {
+                NODE_ENV "production"
+            }
+            ports {
+                public {
+                    http "eighty"
+                }
+            }
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Vec<Service>>(kdl).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "[0].ports.public.http");
+}
+
+#[test]
+fn maps_round_trip() {
+    let services = vec![Service {
+        name: "web".to_owned(),
+        env: BTreeMap::from([("NODE_ENV".to_owned(), "production".to_owned())]),
+        upstreams: BTreeMap::from([(
+            "primary".to_owned(),
+            Upstream {
+                host: "10.0.0.1".to_owned(),
+                weight: 3,
+            },
+        )]),
+        ports: BTreeMap::from([(
+            "public".to_owned(),
+            BTreeMap::from([("http".to_owned(), 80)]),
+        )]),
+    }];
+
+    let kdl = facet_kdl::to_string(&services).unwrap();
+    assert!(kdl.contains(r#"NODE_ENV "production""#), "{kdl}");
+    assert!(kdl.contains(r#"primary "10.0.0.1" weight=3"#), "{kdl}");
+
+    let parsed: Vec<Service> = facet_kdl::from_str(&kdl).unwrap();
+    assert_eq!(parsed, services);
+}
+
+#[test]
+fn unnamed_keys_cannot_be_serialized() {
+    #[derive(Debug, Facet)]
+    struct Weights {
+        #[facet(child)]
+        weights: BTreeMap<Option<String>, u8>,
+    }
+
+    let weights = Weights {
+        weights: BTreeMap::from([(None, 1)]),
+    };
+    let error = facet_kdl::to_string(&weights).unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &KdlSerializeErrorKind::InvalidIdentifier("#null".to_owned())
+    );
+}
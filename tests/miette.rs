@@ -0,0 +1,43 @@
+#![cfg(feature = "miette")]
+
+use facet::Facet;
+use indoc::indoc;
+use miette::Diagnostic;
+
+#[derive(Debug, Facet)]
+struct Config {
+    #[facet(argument)]
+    count: i64,
+}
+
+#[test]
+fn deserialize_error_is_a_diagnostic() {
+    let kdl = indoc! {r#"
+        count "many"
+    "#};
+
+    let error = facet_kdl::from_str::<Config>(kdl).unwrap_err();
+
+    assert_eq!(error.code().unwrap().to_string(), "facet_kdl::mismatch");
+    assert!(error.help().is_some());
+    assert!(error.source_code().is_some());
+
+    let labels: Vec<_> = error.labels().unwrap().collect();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(
+        &kdl[labels[0].offset()..][..labels[0].len()].trim(),
+        &r#""many""#
+    );
+}
+
+#[test]
+fn parse_error_passes_through_kdl_diagnostics() {
+    let kdl = indoc! {r#"
+        count 1 {
+    "#};
+
+    let error = facet_kdl::from_str::<Config>(kdl).unwrap_err();
+
+    assert_eq!(error.code().unwrap().to_string(), "facet_kdl::parse");
+    assert!(error.related().unwrap().count() > 0);
+}
@@ -0,0 +1,97 @@
+use facet::Facet;
+use facet_kdl::{KdlErrorKind, Role};
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Limits {
+    #[facet(property)]
+    cpu: u32,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Service {
+    #[facet(argument)]
+    name: String,
+    #[facet(property)]
+    port: Option<u16>,
+    #[facet(property)]
+    retries: i32,
+    #[facet(child)]
+    description: Option<String>,
+    #[facet(child)]
+    limits: Option<Limits>,
+}
+
+#[test]
+fn missing_values_are_none() {
+    let services: Vec<Service> = facet_kdl::from_str(r#"service "db" retries=3"#).unwrap();
+    assert_eq!(
+        services,
+        [Service {
+            name: "db".to_owned(),
+            port: None,
+            retries: 3,
+            description: None,
+            limits: None,
+        }]
+    );
+}
+
+#[test]
+fn null_values_are_none() {
+    let kdl = indoc! {r#"
+        service "db" port=#null retries=3 {
+            description #null
+        }
+    "#};
+
+    let services: Vec<Service> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(services[0].port, None);
+    assert_eq!(services[0].description, None);
+}
+
+#[test]
+fn present_values_are_some() {
+    let kdl = indoc! {r#"
+        service "db" port=5432 retries=3 {
+            description "Primary database"
+            limits cpu=2
+        }
+    "#};
+
+    let services: Vec<Service> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(services[0].port, Some(5432));
+    assert_eq!(services[0].description.as_deref(), Some("Primary database"));
+    assert_eq!(services[0].limits, Some(Limits { cpu: 2 }));
+}
+
+#[test]
+fn null_is_not_a_default() {
+    let error = facet_kdl::from_str::<Vec<Service>>(r#"service "db" retries=#null"#).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::Mismatch {
+            target, expected, ..
+        } => {
+            assert_eq!(target.role, Role::Property);
+            assert_eq!(target.field, "retries");
+            assert_eq!(expected.type_identifier, "i32");
+        }
+        other => panic!("expected a mismatch, got {other:?}"),
+    }
+    assert!(error.to_string().contains("#null"), "{error}");
+}
+
+#[test]
+fn options_round_trip() {
+    let services = vec![Service {
+        name: "db".to_owned(),
+        port: Some(5432),
+        retries: 3,
+        description: Some("Primary database".to_owned()),
+        limits: Some(Limits { cpu: 2 }),
+    }];
+
+    let kdl = facet_kdl::to_string(&services).unwrap();
+    assert!(kdl.contains(r#"service "db" port=5432 retries=3"#), "{kdl}");
+    assert_eq!(facet_kdl::from_str::<Vec<Service>>(&kdl).unwrap(), services);
+}
@@ -0,0 +1,115 @@
+use std::collections::{BTreeMap, HashMap};
+
+use facet::Facet;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Plugins {
+    #[facet(child)]
+    plugin: Plugin,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Plugin {
+    #[facet(argument)]
+    name: String,
+    #[facet(property, default)]
+    enabled: bool,
+    #[facet(properties)]
+    options: HashMap<String, String>,
+}
+
+#[test]
+fn unknown_properties_are_collected() {
+    let kdl = r#"plugin "cache" enabled=#true backend="redis" url="redis://localhost""#;
+
+    let plugins: Plugins = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(plugins.plugin.name, "cache");
+    assert!(plugins.plugin.enabled);
+    assert_eq!(
+        plugins.plugin.options,
+        HashMap::from([
+            ("backend".to_owned(), "redis".to_owned()),
+            ("url".to_owned(), "redis://localhost".to_owned()),
+        ])
+    );
+}
+
+#[test]
+fn no_unknown_properties_is_empty() {
+    let plugins: Plugins = facet_kdl::from_str(r#"plugin "noop""#).unwrap();
+    assert!(plugins.plugin.options.is_empty());
+}
+
+#[test]
+fn collected_values_are_typed() {
+    #[derive(Debug, Facet)]
+    struct Limits {
+        #[facet(child)]
+        limits: Caps,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Caps {
+        #[facet(properties)]
+        caps: BTreeMap<String, u32>,
+    }
+
+    let limits: Limits = facet_kdl::from_str("limits cpu=2 memory=512").unwrap();
+    assert_eq!(
+        limits.limits.caps,
+        BTreeMap::from([("cpu".to_owned(), 2), ("memory".to_owned(), 512)])
+    );
+
+    let error = facet_kdl::from_str::<Limits>(r#"limits cpu=2 memory="lots""#).unwrap_err();
+    assert_eq!(error.path().unwrap().to_string(), "limits.caps.memory");
+
+    let errors =
+        facet_kdl::from_str_all_errors::<Limits>(r#"limits cpu="one" memory="lots""#).unwrap_err();
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+}
+
+#[test]
+fn collected_properties_serialize_as_properties() {
+    #[derive(Debug, Facet)]
+    struct Theme {
+        #[facet(child)]
+        colors: Colors,
+    }
+
+    #[derive(Debug, Facet)]
+    struct Colors {
+        #[facet(properties)]
+        named: BTreeMap<String, String>,
+    }
+
+    let theme = Theme {
+        colors: Colors {
+            named: BTreeMap::from([
+                ("accent".to_owned(), "#ff8800".to_owned()),
+                ("background".to_owned(), "#101010".to_owned()),
+            ]),
+        },
+    };
+
+    let kdl = facet_kdl::to_string(&theme).unwrap();
+    assert!(
+        kdl.contains(r##"colors accent="#ff8800" background="#101010""##),
+        "{kdl}"
+    );
+}
+
+#[test]
+fn properties_named_like_other_fields_are_collected() {
+    let kdl = r#"plugin "cache" name="shadow" options="all" enabled=#true"#;
+
+    let plugins: Plugins = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(plugins.plugin.name, "cache");
+    assert!(plugins.plugin.enabled);
+    assert_eq!(
+        plugins.plugin.options,
+        HashMap::from([
+            ("name".to_owned(), "shadow".to_owned()),
+            ("options".to_owned(), "all".to_owned()),
+        ])
+    );
+}
@@ -0,0 +1,72 @@
+use facet::Facet;
+use facet_kdl::{KdlSerializeErrorKind, PathSegment};
+
+#[derive(Debug, Facet)]
+struct Quota {
+    #[facet(child)]
+    limits: Limits,
+}
+
+#[derive(Debug, Facet)]
+struct Limits {
+    #[facet(property)]
+    max: u128,
+}
+
+#[test]
+fn out_of_range_numbers_report_their_field() {
+    let quota = Quota {
+        limits: Limits { max: u128::MAX },
+    };
+    let error = facet_kdl::to_string(&quota).unwrap_err();
+
+    assert!(matches!(error.kind(), KdlSerializeErrorKind::OutOfRange(_)));
+    assert_eq!(
+        error.path().segments(),
+        [
+            PathSegment::Field("limits".to_owned()),
+            PathSegment::Field("max".to_owned())
+        ]
+    );
+    assert!(error.to_string().contains("`limits.max`"));
+}
+
+#[derive(Debug, Facet)]
+struct Server {
+    #[facet(property)]
+    listen: Listen,
+}
+
+#[derive(Debug, Facet)]
+struct Listen {
+    #[facet(property)]
+    port: u16,
+}
+
+#[test]
+fn structs_cannot_be_properties() {
+    let server = Server {
+        listen: Listen { port: 8080 },
+    };
+    let error = facet_kdl::to_string(&server).unwrap_err();
+
+    assert_eq!(
+        error.kind(),
+        &KdlSerializeErrorKind::RoleConflict {
+            field: "listen",
+            role: "property",
+            found: "a struct",
+        }
+    );
+    assert_eq!(error.path().to_string(), "listen");
+}
+
+#[test]
+fn values_in_range_still_serialize() {
+    let quota = Quota {
+        limits: Limits { max: 1 << 100 },
+    };
+    let kdl = facet_kdl::to_string(&quota).expect("Failed to serialize");
+
+    assert!(kdl.contains(&(1u128 << 100).to_string()));
+}
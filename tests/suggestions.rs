@@ -0,0 +1,65 @@
+use facet::Facet;
+use indoc::indoc;
+
+#[derive(Debug, Facet)]
+struct Config {
+    #[facet(child)]
+    process: Process,
+}
+
+#[derive(Debug, Facet)]
+struct Process {
+    #[facet(argument)]
+    id: String,
+    #[facet(property)]
+    command: String,
+    #[facet(property, rename = "working-dir")]
+    working_dir: String,
+}
+
+#[test]
+fn misspelled_property_suggests_field() {
+    let kdl = indoc! {r#"
+        process "web" comand="/usr/bin/node" working-dir="/srv"
+    "#};
+
+    let error = facet_kdl::from_str::<Config>(kdl).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(message.starts_with("unknown property `comand` on `Process`; did you mean `command`?"));
+}
+
+#[test]
+fn misspelled_child_node_uses_renamed_field() {
+    let kdl = indoc! {r#"
+        process "web" {
+            command "/usr/bin/node"
+            working-dri "/srv"
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Config>(kdl).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(
+        message.starts_with(
+            "unknown child node `working-dri` on `Process`; did you mean `working-dir`?"
+        )
+    );
+}
+
+#[test]
+fn unrelated_name_has_no_suggestion() {
+    let kdl = indoc! {r#"
+        process "web" command="/usr/bin/node" working-dir="/srv" restart=#true
+    "#};
+
+    let error = facet_kdl::from_str::<Config>(kdl).unwrap_err();
+    let message = error.to_string();
+    println!("{message}");
+
+    assert!(message.starts_with("unknown property `restart` on `Process`"));
+    assert!(!message.contains("did you mean"));
+}
@@ -0,0 +1,104 @@
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+struct S3 {
+    #[facet(property)]
+    bucket: String,
+    #[facet(property)]
+    region: String,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+enum Backend {
+    S3(S3),
+    Local {
+        #[facet(property)]
+        path: String,
+        #[facet(child, default)]
+        quota: u64,
+    },
+    Memory,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Storage {
+    #[facet(child, tag = "type")]
+    backend: Backend,
+    #[facet(children = "mirror", tag = "kind")]
+    mirrors: Vec<Backend>,
+}
+
+#[test]
+fn tag_properties_select_variants() {
+    let kdl = indoc! {r#"
+        backend type="local" path="/var/data" {
+            quota 1024
+        }
+        mirror bucket="logs" kind="s3" region="eu-west-1"
+        mirror kind="memory"
+    "#};
+
+    let storage: Storage = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        storage,
+        Storage {
+            backend: Backend::Local {
+                path: "/var/data".to_owned(),
+                quota: 1024,
+            },
+            mirrors: vec![
+                Backend::S3(S3 {
+                    bucket: "logs".to_owned(),
+                    region: "eu-west-1".to_owned(),
+                }),
+                Backend::Memory,
+            ],
+        }
+    );
+}
+
+#[test]
+fn missing_tags_are_reported() {
+    let error = facet_kdl::from_str::<Storage>(r#"backend path="/var/data""#).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::MissingNodes(missing) => {
+            assert_eq!(missing, &["property `type` of `backend`"]);
+        }
+        other => panic!("expected a missing tag, got {other:?}"),
+    }
+}
+
+#[test]
+fn unknown_tags_list_the_variants() {
+    let error = facet_kdl::from_str::<Storage>(r#"backend type="gcs""#).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::UnknownVariant { name, expected, .. } => {
+            assert_eq!(name, "gcs");
+            assert_eq!(expected, &["s3", "local", "memory"]);
+        }
+        other => panic!("expected an unknown variant, got {other:?}"),
+    }
+}
+
+#[test]
+fn tags_are_written_first() {
+    let storage = Storage {
+        backend: Backend::S3(S3 {
+            bucket: "logs".to_owned(),
+            region: "eu-west-1".to_owned(),
+        }),
+        mirrors: vec![Backend::Memory],
+    };
+
+    let kdl = facet_kdl::to_string(&storage).unwrap();
+    assert!(
+        kdl.contains(r#"backend type="s3" bucket="logs" region="eu-west-1""#),
+        "{kdl}"
+    );
+    assert!(kdl.contains(r#"mirror kind="memory""#), "{kdl}");
+    assert_eq!(facet_kdl::from_str::<Storage>(&kdl).unwrap(), storage);
+}
@@ -0,0 +1,121 @@
+use facet::Facet;
+use facet_kdl::{KdlErrorKind, Role};
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Rgb(u8, u8, u8);
+
+#[derive(Debug, Facet, PartialEq)]
+struct Sprite {
+    #[facet(argument)]
+    name: String,
+    #[facet(child)]
+    position: (f64, f64),
+    #[facet(child)]
+    tint: Rgb,
+    #[facet(child)]
+    size: [u32; 2],
+}
+
+#[test]
+fn arguments_fill_tuples_and_arrays() {
+    let kdl = indoc! {r#"
+        sprite "player" {
+            position 1.5 -2.0
+            tint 255 128 0
+            size 32 48
+        }
+    "#};
+
+    let sprites: Vec<Sprite> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        sprites,
+        [Sprite {
+            name: "player".to_owned(),
+            position: (1.5, -2.0),
+            tint: Rgb(255, 128, 0),
+            size: [32, 48],
+        }]
+    );
+}
+
+#[test]
+fn lists_of_tuples_are_one_node_each() {
+    let points: Vec<(i32, i32)> = facet_kdl::from_str("point 0 0\npoint 3 4").unwrap();
+    assert_eq!(points, [(0, 0), (3, 4)]);
+}
+
+#[test]
+fn arity_mismatches_give_both_counts() {
+    let kdl = indoc! {r#"
+        sprite "player" {
+            position 1.5 -2.0
+            tint 255 128
+            size 32 48
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Vec<Sprite>>(kdl).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::Arity {
+            field,
+            expected,
+            found,
+            ..
+        } => {
+            assert_eq!(field, "tint");
+            assert_eq!((*expected, *found), (3, 2));
+        }
+        other => panic!("expected an arity error, got {other:?}"),
+    }
+    assert_eq!(
+        error.to_string().lines().next().unwrap(),
+        "`tint` of `Sprite` takes 3 arguments, but found 2"
+    );
+
+    let error = facet_kdl::from_str::<Vec<(i32, i32)>>("point 0 0 0").unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        KdlErrorKind::Arity {
+            expected: 2,
+            found: 3,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn elements_are_checked_by_position() {
+    let kdl = indoc! {r#"
+        sprite "player" {
+            position 1.5 "left"
+            tint 255 128 0
+            size 32 48
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Vec<Sprite>>(kdl).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::Mismatch { target, .. } => {
+            assert_eq!(target.role, Role::Argument(1));
+            assert_eq!(target.field, "position");
+        }
+        other => panic!("expected a mismatch, got {other:?}"),
+    }
+}
+
+#[test]
+fn tuples_and_arrays_are_written_as_arguments() {
+    let sprites = vec![Sprite {
+        name: "player".to_owned(),
+        position: (1.5, -2.0),
+        tint: Rgb(255, 128, 0),
+        size: [32, 48],
+    }];
+
+    let kdl = facet_kdl::to_string(&sprites).unwrap();
+    assert!(kdl.contains("position 1.5 -2.0"), "{kdl}");
+    assert!(kdl.contains("tint 255 128 0"), "{kdl}");
+    assert!(kdl.contains("size 32 48"), "{kdl}");
+    assert_eq!(facet_kdl::from_str::<Vec<Sprite>>(&kdl).unwrap(), sprites);
+}
@@ -0,0 +1,103 @@
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "kebab-case")]
+enum Mode {
+    Fast,
+    SafeAndSlow,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "lowercase")]
+enum Level {
+    Debug,
+    Info,
+    #[facet(rename = "warn")]
+    Warning,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Logger {
+    #[facet(argument)]
+    name: String,
+    #[facet(property)]
+    level: Level,
+    #[facet(child)]
+    mode: Mode,
+}
+
+#[test]
+fn variant_names_fill_arguments_and_properties() {
+    let kdl = indoc! {r#"
+        logger "app" level="warn" {
+            mode "safe-and-slow"
+        }
+    "#};
+
+    let loggers: Vec<Logger> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(
+        loggers,
+        [Logger {
+            name: "app".to_owned(),
+            level: Level::Warning,
+            mode: Mode::SafeAndSlow,
+        }]
+    );
+}
+
+#[test]
+fn unknown_names_list_the_variants() {
+    let kdl = indoc! {r#"
+        logger "app" level="warning" {
+            mode "fast"
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Vec<Logger>>(kdl).unwrap_err();
+    match error.kind() {
+        KdlErrorKind::UnknownVariant { name, expected, .. } => {
+            assert_eq!(name, "warning");
+            assert_eq!(expected, &["debug", "info", "warn"]);
+        }
+        other => panic!("expected an unknown variant, got {other:?}"),
+    }
+    assert!(
+        error
+            .to_string()
+            .contains("expected one of `debug`, `info`, or `warn`"),
+        "{error}"
+    );
+    assert_eq!(error.path().unwrap().to_string(), "[0].level");
+}
+
+#[test]
+fn lists_hold_variant_names() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Profile {
+        #[facet(argument)]
+        name: String,
+        #[facet(arguments)]
+        modes: Vec<Mode>,
+    }
+
+    let kdl = r#"profile "batch" "fast" "safe-and-slow""#;
+    let profiles: Vec<Profile> = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(profiles[0].modes, [Mode::Fast, Mode::SafeAndSlow]);
+}
+
+#[test]
+fn unit_enums_round_trip() {
+    let loggers = vec![Logger {
+        name: "app".to_owned(),
+        level: Level::Debug,
+        mode: Mode::Fast,
+    }];
+
+    let kdl = facet_kdl::to_string(&loggers).unwrap();
+    assert!(kdl.contains(r#"level="debug""#), "{kdl}");
+    assert_eq!(facet_kdl::from_str::<Vec<Logger>>(&kdl).unwrap(), loggers);
+}
@@ -0,0 +1,159 @@
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+use indoc::indoc;
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+enum Port {
+    Number(u16),
+    Address {
+        #[facet(property)]
+        host: String,
+        #[facet(property)]
+        number: u16,
+    },
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Listener {
+    #[facet(argument)]
+    name: String,
+    #[facet(child, untagged)]
+    port: Port,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Config {
+    #[facet(children = "listener")]
+    listeners: Vec<Listener>,
+}
+
+#[test]
+fn the_first_variant_that_fits_wins() {
+    let kdl = indoc! {r#"
+        listener "legacy" {
+            port 8080
+        }
+        listener "public" {
+            port host="0.0.0.0" number=443
+        }
+    "#};
+
+    let config: Config = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(config.listeners[0].port, Port::Number(8080));
+    assert_eq!(
+        config.listeners[1].port,
+        Port::Address {
+            host: "0.0.0.0".to_owned(),
+            number: 443
+        }
+    );
+}
+
+#[test]
+fn values_can_be_untagged_too() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Upstream {
+        #[facet(property, untagged)]
+        port: Port,
+    }
+
+    let upstreams: Vec<Upstream> = facet_kdl::from_str("upstream port=8080").unwrap();
+    assert_eq!(upstreams[0].port, Port::Number(8080));
+
+    let error = facet_kdl::from_str::<Vec<Upstream>>(r#"upstream port="http""#).unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        KdlErrorKind::NoMatchingVariant { .. }
+    ));
+}
+
+#[test]
+fn rejections_explain_every_variant() {
+    let kdl = indoc! {r#"
+        listener "broken" {
+            port host="0.0.0.0"
+        }
+    "#};
+
+    let error = facet_kdl::from_str::<Config>(kdl).unwrap_err();
+    let KdlErrorKind::NoMatchingVariant { rejections, .. } = error.kind() else {
+        panic!("expected no matching variant, got {:?}", error.kind());
+    };
+    let tried: Vec<_> = rejections.iter().map(|(variant, _)| *variant).collect();
+    assert_eq!(tried, ["number", "address"]);
+    assert!(matches!(
+        rejections[0].1.kind(),
+        KdlErrorKind::UnknownField { .. }
+    ));
+    assert!(matches!(
+        rejections[1].1.kind(),
+        KdlErrorKind::MissingNodes(_)
+    ));
+
+    let message = error.to_string();
+    assert!(message.contains("- `number`:"), "{message}");
+    assert!(message.contains("- `address`:"), "{message}");
+}
+
+#[test]
+fn untagged_enums_round_trip() {
+    let config = Config {
+        listeners: vec![
+            Listener {
+                name: "legacy".to_owned(),
+                port: Port::Number(8080),
+            },
+            Listener {
+                name: "public".to_owned(),
+                port: Port::Address {
+                    host: "0.0.0.0".to_owned(),
+                    number: 443,
+                },
+            },
+        ],
+    };
+
+    let kdl = facet_kdl::to_string(&config).unwrap();
+    assert!(kdl.contains("port 8080"), "{kdl}");
+    assert!(kdl.contains(r#"port host="0.0.0.0" number=443"#), "{kdl}");
+    assert_eq!(facet_kdl::from_str::<Config>(&kdl).unwrap(), config);
+}
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+enum Tree {
+    Leaf(u32),
+    Branch {
+        #[facet(children, untagged)]
+        items: Vec<Tree>,
+    },
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Forest {
+    #[facet(children = "tree", untagged)]
+    trees: Vec<Tree>,
+}
+
+#[test]
+fn nested_untagged_enums_are_tried_once_per_level() {
+    // NOTE: Trying every level again for each level around it would take about 2^30 attempts here
+    let depth = 30;
+    let kdl = format!(
+        "tree {{ {} item 7 {} }}",
+        "item { ".repeat(depth - 1),
+        "} ".repeat(depth - 1)
+    );
+
+    let forest: Forest = facet_kdl::from_str(&kdl).unwrap();
+    let mut tree = &forest.trees[0];
+    let mut branches = 0;
+    while let Tree::Branch { items } = tree {
+        branches += 1;
+        tree = &items[0];
+    }
+    assert_eq!(branches, depth);
+    assert_eq!(*tree, Tree::Leaf(7));
+}
@@ -0,0 +1,145 @@
+//! Malformed and mismatched documents must produce errors rather than panics.
+//!
+//! These tests build, partially build and then abandon owned values like `String`s, so they're also worth running
+//! under Miri to check that nothing is leaked, double-freed or read uninitialised: `cargo +nightly miri test --test
+//! untrusted_input`.
+
+use std::borrow::Cow;
+
+use facet::Facet;
+use facet_kdl::KdlErrorKind;
+
+#[derive(Debug, Facet, PartialEq)]
+struct Config {
+    #[facet(child)]
+    server: Server,
+    #[facet(child)]
+    motd: Cow<'static, str>,
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Server {
+    #[facet(argument)]
+    host: String,
+    #[facet(property)]
+    port: u16,
+    #[facet(child)]
+    initial: char,
+}
+
+#[test]
+fn owned_values_are_built() {
+    let kdl = r#"
+        server "localhost" port=8080 {
+            initial "é"
+        }
+        motd "hello"
+    "#;
+
+    let config: Config = facet_kdl::from_str(kdl).unwrap();
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.initial, 'é');
+    assert_eq!(config.motd, "hello");
+}
+
+#[test]
+fn partially_built_values_are_dropped() {
+    // NOTE: `host` is already allocated by the time `port` fails
+    let kdl = r#"server "localhost" port="http""#;
+    assert!(facet_kdl::from_str::<Config>(kdl).is_err());
+
+    let kdl = r#"
+        motd "hello"
+        server "localhost" port=8080 {
+            initial "too long"
+        }
+    "#;
+    assert!(facet_kdl::from_str::<Config>(kdl).is_err());
+    assert!(facet_kdl::from_str_all_errors::<Config>(kdl).is_err());
+}
+
+#[test]
+fn repeated_entries_replace_each_other() {
+    let kdl = r#"
+        server "first" "second" port=1 port=2 {
+            initial "a"
+        }
+        motd "old"
+        motd "new"
+    "#;
+
+    // Whether or not repeats are accepted, the values they replace must be dropped exactly once
+    let _ = facet_kdl::from_str::<Config>(kdl);
+    let _ = facet_kdl::from_str_all_errors::<Config>(kdl);
+}
+
+#[test]
+fn unsupported_roots_are_errors() {
+    let error = facet_kdl::from_str::<u32>("node 1").unwrap_err();
+    assert!(matches!(error.kind(), KdlErrorKind::UnsupportedRoot(_)));
+
+    let error = facet_kdl::from_str::<String>("").unwrap_err();
+    assert!(matches!(error.kind(), KdlErrorKind::UnsupportedRoot(_)));
+}
+
+#[test]
+fn garbage_is_an_error() {
+    let inputs = [
+        "",
+        "{",
+        "}",
+        "server",
+        "server {",
+        "server \"a\" port=99999999999999999999999999999999999999999",
+        "server \"a\" port=-1 { initial \"\" }",
+        "server #null port=#null { initial #null }",
+        "server 1.5 port=1.5 { initial 1 }",
+        "server \"a\" port=1 { initial \"a\"; initial \"b\" }",
+        "server { server { server { } } }",
+        "/- server \"a\" port=1",
+        "\u{0}\u{feff}\u{1F600}",
+    ];
+
+    for kdl in inputs {
+        assert!(
+            facet_kdl::from_str::<Config>(kdl).is_err(),
+            "{kdl:?} should not deserialize"
+        );
+        assert!(facet_kdl::from_str_all_errors::<Config>(kdl).is_err());
+    }
+}
+
+#[derive(Debug, Facet, PartialEq)]
+#[repr(u8)]
+#[facet(rename_all = "snake_case")]
+enum Step {
+    Run(String),
+    Parallel(Vec<Step>),
+}
+
+#[derive(Debug, Facet, PartialEq)]
+struct Pipeline {
+    #[facet(argument)]
+    name: String,
+    steps: Vec<Step>,
+}
+
+/// A pipeline with `depth` levels of `parallel` steps around a single `run` step.
+fn nested_pipeline(depth: usize) -> String {
+    format!(
+        "pipeline \"deep\" {{ {} run \"make\" {} }}",
+        "parallel { ".repeat(depth),
+        "} ".repeat(depth)
+    )
+}
+
+#[test]
+fn deep_nesting_is_an_error() {
+    let pipelines: Vec<Pipeline> = facet_kdl::from_str(&nested_pipeline(10)).unwrap();
+    assert_eq!(pipelines[0].name, "deep");
+
+    let kdl = nested_pipeline(100_000);
+    let error = facet_kdl::from_str::<Vec<Pipeline>>(&kdl).unwrap_err();
+    assert!(matches!(error.kind(), KdlErrorKind::TooDeep(_)));
+    assert!(facet_kdl::from_str_all_errors::<Vec<Pipeline>>(&kdl).is_err());
+}